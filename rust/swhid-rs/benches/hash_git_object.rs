@@ -0,0 +1,22 @@
+//! Benchmarks the per-object overhead of `hash_git_object` on many tiny
+//! blobs, the workload that motivated replacing the `format!`-allocated
+//! length header with a stack-buffer one in `hash.rs`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use swhid::hash_git_object;
+
+fn hash_many_tiny_blobs(n: usize) {
+    for i in 0..n {
+        let data = [(i % 256) as u8; 16];
+        black_box(hash_git_object("blob", &data));
+    }
+}
+
+fn bench_hash_git_object(c: &mut Criterion) {
+    c.bench_function("hash_git_object/100k tiny blobs", |b| {
+        b.iter(|| hash_many_tiny_blobs(100_000));
+    });
+}
+
+criterion_group!(benches, bench_hash_git_object);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! Write every SWHID in a git working tree — content, directories, and the
+//! `HEAD` commit when one can be resolved — to stdout, sorted and
+//! deduplicated, one per line.
+//!
+//! ```text
+//! cargo run --example dump_repository_swhids -- /path/to/repo
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use swhid::SwhidComputer;
+
+fn main() -> ExitCode {
+    let Some(repo_path) = std::env::args().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: dump_repository_swhids <repo-path>");
+        return ExitCode::FAILURE;
+    };
+
+    let stdout = std::io::stdout();
+    let mut writer = stdout.lock();
+    match SwhidComputer::new().dump_repository_swhids(&repo_path, &mut writer) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
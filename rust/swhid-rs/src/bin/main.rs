@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use swhid::SwhidComputer;
+
+/// The `--type` values accepted for `-` (stdin) input, where there's no
+/// path on disk to tell a file from a directory.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StdinType {
+    Content,
+    Directory,
+}
+
+struct Args {
+    path: PathBuf,
+    json: bool,
+    stdin_type: StdinType,
+}
+
+fn parse_args() -> Option<Args> {
+    let mut path = None;
+    let mut json = false;
+    let mut stdin_type = StdinType::Content;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--type" => {
+                stdin_type = match args.next()?.as_str() {
+                    "cnt" => StdinType::Content,
+                    "dir" => StdinType::Directory,
+                    _ => return None,
+                };
+            }
+            other => path = Some(PathBuf::from(other)),
+        }
+    }
+    Some(Args {
+        path: path?,
+        json,
+        stdin_type,
+    })
+}
+
+fn print_error(path: &std::path::Path, err: &swhid::SwhidError, json: bool) {
+    if json {
+        let obj = serde_json::json!({
+            "path": path.display().to_string(),
+            "error": err.to_string(),
+        });
+        eprintln!("{obj}");
+    } else {
+        eprintln!("{err}");
+    }
+}
+
+/// Read stdin as either raw content or, with `--type dir`, a tar stream
+/// (`tar c dir | swhid - --type dir`) whose top-level directory SWHID is
+/// computed without ever touching disk.
+fn compute_stdin_swhid(stdin_type: StdinType) -> Result<swhid::Swhid, swhid::SwhidError> {
+    use std::io::Read;
+
+    match stdin_type {
+        StdinType::Content => {
+            let mut data = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut data)
+                .map_err(|e| swhid::SwhidError::io(PathBuf::from("<stdin>"), e))?;
+            Ok(swhid::Content::from_data(data).swhid())
+        }
+        StdinType::Directory => {
+            #[cfg(feature = "tar")]
+            {
+                SwhidComputer::new().compute_tar_swhid(std::io::stdin())
+            }
+            #[cfg(not(feature = "tar"))]
+            {
+                Err(swhid::SwhidError::InvalidGitObject(
+                    "reading `-` as `--type dir` requires the `tar` feature".into(),
+                ))
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Some(args) => args,
+        None => {
+            eprintln!("usage: swhid [--json] [--type cnt|dir] <path|->");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if args.path == Path::new("-") {
+        return match compute_stdin_swhid(args.stdin_type) {
+            Ok(swhid) => {
+                println!("{swhid}");
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                print_error(&args.path, &err, args.json);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Normalize the trailing separator up front so error messages and any
+    // path echoed back to the caller look the same for `mydir` and `mydir/`.
+    let path: std::path::PathBuf = args
+        .path
+        .to_string_lossy()
+        .trim_end_matches(std::path::MAIN_SEPARATOR)
+        .into();
+    let path = if path.as_os_str().is_empty() {
+        args.path.clone()
+    } else {
+        path
+    };
+
+    let computer = SwhidComputer::new();
+    let result = if path.is_dir() {
+        computer.compute_directory_swhid(&path)
+    } else {
+        computer.compute_file_swhid(&path)
+    };
+
+    match result {
+        Ok(swhid) => {
+            println!("{swhid}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            print_error(&path, &err, args.json);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,2536 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::content::Content;
+use crate::directory::{hash_symlink_target, Directory, Permissions};
+use crate::error::SwhidError;
+use crate::glob::glob_match;
+use crate::swhid::Swhid;
+
+/// A cache of content hashes keyed by a file's path, mtime and length, so
+/// [`SwhidComputer::compute_directory_swhid`] and friends can skip re-reading
+/// and re-hashing files that haven't changed since the last run. Consulted
+/// in [`SwhidComputer::hash_entry`]; a cache miss (or no cache at all, the
+/// default) just falls back to hashing the file as usual.
+///
+/// Mtime and length are a heuristic, not a guarantee: a file rewritten with
+/// identical size within the same mtime granularity will report a stale
+/// cache hit. Callers that can't tolerate that should not install a cache.
+pub trait ContentCache: Send + Sync {
+    /// Look up a previously cached hash for `path`, valid only if `mtime`
+    /// and `len` still match what was cached.
+    fn get(&self, path: &Path, mtime: SystemTime, len: u64) -> Option<[u8; 20]>;
+
+    /// Record `hash` as the content hash of `path` as of `mtime`/`len`.
+    fn put(&self, path: &Path, mtime: SystemTime, len: u64, hash: [u8; 20]);
+}
+
+type CacheEntry = (SystemTime, u64, [u8; 20]);
+
+/// A simple in-memory [`ContentCache`], backed by a [`Mutex`]-guarded
+/// [`std::collections::HashMap`]. Doesn't persist across process runs; wrap
+/// a different backing store (a file, `sled`, ...) behind [`ContentCache`]
+/// for that.
+#[derive(Default)]
+pub struct InMemoryContentCache {
+    entries: Mutex<std::collections::HashMap<PathBuf, CacheEntry>>,
+}
+
+impl InMemoryContentCache {
+    pub fn new() -> Self {
+        InMemoryContentCache::default()
+    }
+}
+
+impl ContentCache for InMemoryContentCache {
+    fn get(&self, path: &Path, mtime: SystemTime, len: u64) -> Option<[u8; 20]> {
+        let entries = self.entries.lock().unwrap();
+        let (cached_mtime, cached_len, hash) = entries.get(path)?;
+        if *cached_mtime == mtime && *cached_len == len {
+            Some(*hash)
+        } else {
+            None
+        }
+    }
+
+    fn put(&self, path: &Path, mtime: SystemTime, len: u64, hash: [u8; 20]) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(path.to_path_buf(), (mtime, len, hash));
+    }
+}
+
+#[cfg(unix)]
+fn raw_mode_permissions(metadata: &fs::Metadata) -> crate::directory::Permissions {
+    use std::os::unix::fs::MetadataExt;
+    crate::directory::Permissions::from_mode(metadata.mode())
+}
+
+#[cfg(not(unix))]
+fn raw_mode_permissions(_metadata: &fs::Metadata) -> crate::directory::Permissions {
+    crate::directory::Permissions::Regular
+}
+
+/// Strip trailing/redundant path separators and `.` components from `path`
+/// so `mydir/`, `mydir/.` and `mydir` are all treated identically, both for
+/// hashing and for any path formatted back out to the caller. Doesn't touch
+/// `..` components, since resolving those requires knowing the filesystem
+/// layout (symlinks) and isn't this function's job.
+fn normalize_root(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        if component == Component::CurDir {
+            continue;
+        }
+        normalized.push(component.as_os_str());
+    }
+    if normalized.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        normalized
+    }
+}
+
+
+/// What kind of object a path resolved to during traversal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeObject {
+    Content(Swhid),
+    Directory(Swhid),
+}
+
+impl TreeObject {
+    pub fn swhid(&self) -> &Swhid {
+        match self {
+            TreeObject::Content(swhid) => swhid,
+            TreeObject::Directory(swhid) => swhid,
+        }
+    }
+
+    /// The owned `Swhid` identifying this object, for use as a key (e.g.
+    /// wrapping this object in an [`IdentifiedObject`] for a `HashSet`).
+    /// Like [`TreeObject::swhid`], but returns an owned copy instead of a
+    /// reference.
+    pub fn swhid_key(&self) -> Swhid {
+        *self.swhid()
+    }
+}
+
+/// One row of [`SwhidComputer::traverse_as_ls_tree`], mirroring a line of
+/// `git ls-tree -r -t <tree>` output: `mode` and `kind` use git's exact
+/// tokens ("040000"/"tree" for a directory, "100644"/"100755"/"120000" with
+/// "blob" for a file/executable/symlink), and `path` is relative to the
+/// traversal root using `/` separators regardless of platform, so the
+/// `Vec` can be formatted and diffed line-for-line against real
+/// `git ls-tree` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LsTreeEntry {
+    pub mode: &'static str,
+    pub kind: &'static str,
+    pub hash_hex: String,
+    pub path: String,
+}
+
+/// A [`TreeObject`] paired with its [`Swhid`], with `Hash`/`Eq` defined
+/// purely in terms of the id: two `IdentifiedObject`s are equal (and hash
+/// equal) whenever their SWHIDs match, regardless of anything else about
+/// how the wrapped `TreeObject` was produced. Lets discovered objects be
+/// collected into a `HashSet` or deduplicated by identity.
+#[derive(Debug, Clone)]
+pub struct IdentifiedObject(pub Swhid, pub TreeObject);
+
+impl IdentifiedObject {
+    pub fn new(object: TreeObject) -> Self {
+        IdentifiedObject(object.swhid_key(), object)
+    }
+}
+
+impl PartialEq for IdentifiedObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for IdentifiedObject {}
+
+impl std::hash::Hash for IdentifiedObject {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        std::hash::Hash::hash(&self.0, state);
+    }
+}
+
+impl std::fmt::Display for TreeObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreeObject::Content(swhid) => write!(f, "content({swhid})"),
+            TreeObject::Directory(swhid) => write!(f, "directory({swhid})"),
+        }
+    }
+}
+
+/// Aggregate stats gathered while walking a tree with
+/// [`SwhidComputer::traverse_directory_with_summary`]: the total size, in
+/// bytes, of every regular/executable file's content. Directories and
+/// symlinks don't have "content" in this sense, so they aren't counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraversalSummary {
+    pub total_content_size: u64,
+}
+
+/// Object-type breakdown gathered while walking a tree with
+/// [`SwhidComputer::traverse_directory_recursively_with_stats`], so callers
+/// (e.g. an ingestion dashboard) don't need a second pass over the
+/// returned `Vec` to tally it up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TraversalStats {
+    /// Regular/executable files, hashed as `cnt` objects.
+    pub content_count: usize,
+    /// Directories, hashed as `dir` objects.
+    pub directory_count: usize,
+    /// Symlinks: also `cnt` objects (their target path is what's hashed),
+    /// but counted separately here since that distinction is otherwise
+    /// lost once folded into [`TreeObject::Content`].
+    pub symlink_count: usize,
+    /// Always `0`: unlike [`SwhidComputer::compute_directory_swhid`],
+    /// traversal has no `.swhignore`/`.git` exclusion support, so nothing
+    /// is ever skipped. Kept on this type so dashboards built against it
+    /// don't need a new field if traversal gains exclusion support later.
+    pub excluded_count: usize,
+    pub total_content_size: u64,
+}
+
+/// A progress callback invoked once per file/directory processed. Wrapped in
+/// an `Arc` (rather than a plain `Box`) so `SwhidComputer` stays `Clone` and
+/// the callback can be shared with a parallel traversal without serializing
+/// it behind a lock.
+type ProgressCallback = Arc<dyn Fn(&Path) + Send + Sync>;
+
+/// Computes SWHIDs for files and directories on disk.
+#[derive(Clone, Default)]
+pub struct SwhidComputer {
+    max_file_size: Option<u64>,
+    swhignore: bool,
+    progress: Option<ProgressCallback>,
+    force_executable: Vec<String>,
+    skip_unreadable: bool,
+    include_git_dir: bool,
+    content_cache: Option<Arc<dyn ContentCache>>,
+    relative_paths: bool,
+}
+
+impl fmt::Debug for SwhidComputer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SwhidComputer")
+            .field("max_file_size", &self.max_file_size)
+            .field("swhignore", &self.swhignore)
+            .field("progress", &self.progress.is_some())
+            .field("force_executable", &self.force_executable)
+            .field("skip_unreadable", &self.skip_unreadable)
+            .field("include_git_dir", &self.include_git_dir)
+            .field("content_cache", &self.content_cache.is_some())
+            .field("relative_paths", &self.relative_paths)
+            .finish()
+    }
+}
+
+/// Builds a [`SwhidComputer`] with all options set in one place, instead of
+/// chaining `with_*` calls (which gets unwieldy as more flags accumulate).
+/// The resulting computer is immutable once built.
+#[derive(Clone, Default)]
+pub struct SwhidComputerBuilder {
+    max_file_size: Option<u64>,
+    swhignore: bool,
+    progress: Option<ProgressCallback>,
+    force_executable: Vec<String>,
+    skip_unreadable: bool,
+    include_git_dir: bool,
+    content_cache: Option<Arc<dyn ContentCache>>,
+    relative_paths: bool,
+}
+
+impl SwhidComputerBuilder {
+    pub fn new() -> Self {
+        SwhidComputerBuilder::default()
+    }
+
+    /// See [`SwhidComputer::with_max_file_size`].
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// See [`SwhidComputer::with_swhignore`].
+    pub fn swhignore(mut self, enabled: bool) -> Self {
+        self.swhignore = enabled;
+        self
+    }
+
+    /// See [`SwhidComputer::with_progress`].
+    pub fn progress(mut self, callback: impl Fn(&Path) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// See [`SwhidComputer::with_force_executable`].
+    pub fn force_executable<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.force_executable = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// See [`SwhidComputer::with_skip_unreadable`].
+    pub fn skip_unreadable(mut self, enabled: bool) -> Self {
+        self.skip_unreadable = enabled;
+        self
+    }
+
+    /// Hash a `.git` entry instead of skipping it, overriding
+    /// [`SwhidComputer::compute_directory_swhid`]'s default exclusion. Off
+    /// (i.e. `.git` stays excluded) by default.
+    pub fn include_git_dir(mut self, enabled: bool) -> Self {
+        self.include_git_dir = enabled;
+        self
+    }
+
+    /// Consult `cache` to skip re-hashing files whose path, mtime and
+    /// length haven't changed since they were last cached, and to store
+    /// newly computed hashes for next time. None by default (every file is
+    /// always hashed). See [`ContentCache`] for the staleness caveats.
+    pub fn content_cache(mut self, cache: impl ContentCache + 'static) -> Self {
+        self.content_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// See [`SwhidComputer::with_relative_paths`].
+    pub fn relative_paths(mut self, enabled: bool) -> Self {
+        self.relative_paths = enabled;
+        self
+    }
+
+    pub fn build(self) -> SwhidComputer {
+        SwhidComputer {
+            max_file_size: self.max_file_size,
+            swhignore: self.swhignore,
+            progress: self.progress,
+            force_executable: self.force_executable,
+            skip_unreadable: self.skip_unreadable,
+            include_git_dir: self.include_git_dir,
+            content_cache: self.content_cache,
+            relative_paths: self.relative_paths,
+        }
+    }
+}
+
+impl From<SwhidComputer> for SwhidComputerBuilder {
+    fn from(computer: SwhidComputer) -> Self {
+        SwhidComputerBuilder {
+            max_file_size: computer.max_file_size,
+            swhignore: computer.swhignore,
+            progress: computer.progress,
+            force_executable: computer.force_executable,
+            skip_unreadable: computer.skip_unreadable,
+            include_git_dir: computer.include_git_dir,
+            content_cache: computer.content_cache,
+            relative_paths: computer.relative_paths,
+        }
+    }
+}
+
+/// The key a traversal result should use for `child_path`, given
+/// [`SwhidComputer::with_relative_paths`]: `child_path` itself when
+/// disabled, or `child_path` stripped of the `root` prefix (becoming `"."`
+/// for `root` itself) when enabled.
+fn traversal_key(root: &Path, child_path: &Path, relative: bool) -> PathBuf {
+    if !relative {
+        return child_path.to_path_buf();
+    }
+    match child_path.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from("."),
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => child_path.to_path_buf(),
+    }
+}
+
+/// Parse a `.swhignore` file's contents into glob patterns, one per
+/// non-empty, non-comment line.
+fn parse_swhignore(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+impl SwhidComputer {
+    pub fn new() -> Self {
+        SwhidComputer::default()
+    }
+
+    /// Start building a `SwhidComputer` with more than one or two options
+    /// set, instead of chaining `with_*` calls on the computer itself.
+    pub fn builder() -> SwhidComputerBuilder {
+        SwhidComputerBuilder::default()
+    }
+
+    /// Refuse to read files larger than `bytes` into memory, returning
+    /// [`SwhidError::FileTooLarge`] instead. Unlimited by default.
+    #[deprecated(note = "use SwhidComputer::builder().max_file_size(...).build() instead")]
+    pub fn with_max_file_size(self, bytes: u64) -> Self {
+        SwhidComputerBuilder::from(self).max_file_size(bytes).build()
+    }
+
+    /// Honor a `.swhignore` file at the root of the tree being hashed: one
+    /// glob pattern per line, blank lines and `#` comments ignored. A
+    /// pattern is matched against the bare entry name wherever it appears in
+    /// the tree, unless it starts with `/`, in which case it's anchored to
+    /// the traversal root and matched against the path relative to that
+    /// root instead (e.g. `/vendor/foo` only excludes that exact path at
+    /// the root, not every `foo` anywhere).
+    #[deprecated(note = "use SwhidComputer::builder().swhignore(...).build() instead")]
+    pub fn with_swhignore(self, enabled: bool) -> Self {
+        SwhidComputerBuilder::from(self).swhignore(enabled).build()
+    }
+
+    /// Invoke `callback` once per file or directory processed by
+    /// [`SwhidComputer::compute_directory_swhid`] or
+    /// [`SwhidComputer::traverse_directory_recursively`], useful for driving
+    /// a progress bar or logging slow entries.
+    #[deprecated(note = "use SwhidComputer::builder().progress(...).build() instead")]
+    pub fn with_progress(self, callback: impl Fn(&Path) + Send + Sync + 'static) -> Self {
+        SwhidComputerBuilder::from(self).progress(callback).build()
+    }
+
+    /// Force filenames matching any of `patterns` (glob `*`/`?`) to be
+    /// hashed as `100755` regardless of their on-disk mode. Meant for
+    /// reproducing a Unix checkout's SWHIDs from a tree checked out on
+    /// Windows (no exec bit) or on a filesystem mounted `noexec`, where the
+    /// real mode would otherwise disagree with the one git originally
+    /// recorded.
+    #[deprecated(note = "use SwhidComputer::builder().force_executable(...).build() instead")]
+    pub fn with_force_executable<I, S>(self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        SwhidComputerBuilder::from(self)
+            .force_executable(patterns)
+            .build()
+    }
+
+    /// When enabled, a directory entry that can't be read due to a
+    /// permission error is omitted from the tree instead of aborting the
+    /// whole computation, when using
+    /// [`SwhidComputer::compute_directory_swhid_lenient`]. Has no effect on
+    /// the other `compute_*`/`traverse_*` methods, which keep failing hard
+    /// on the first unreadable entry. Off by default.
+    #[deprecated(note = "use SwhidComputer::builder().skip_unreadable(...).build() instead")]
+    pub fn with_skip_unreadable(self, enabled: bool) -> Self {
+        SwhidComputerBuilder::from(self)
+            .skip_unreadable(enabled)
+            .build()
+    }
+
+    /// See [`SwhidComputerBuilder::include_git_dir`].
+    #[deprecated(note = "use SwhidComputer::builder().include_git_dir(...).build() instead")]
+    pub fn with_include_git_dir(self, enabled: bool) -> Self {
+        SwhidComputerBuilder::from(self)
+            .include_git_dir(enabled)
+            .build()
+    }
+
+    /// Make [`SwhidComputer::traverse_directory_recursively`],
+    /// [`SwhidComputer::traverse_directory_with_summary`] and
+    /// [`SwhidComputer::traverse_directory_recursively_with_stats`] key
+    /// their results by the path relative to the traversal root instead of
+    /// the absolute path, so the output is reproducible across machines
+    /// that have the tree checked out under different absolute prefixes
+    /// (e.g. for a log or JSON blob compared byte-for-byte in CI). The root
+    /// itself is keyed `"."` rather than an empty path. Off by default
+    /// (paths stay absolute, the historical behavior).
+    #[deprecated(note = "use SwhidComputer::builder().relative_paths(...).build() instead")]
+    pub fn with_relative_paths(self, enabled: bool) -> Self {
+        SwhidComputerBuilder::from(self)
+            .relative_paths(enabled)
+            .build()
+    }
+
+    fn apply_force_executable(&self, path: &Path, permissions: Permissions) -> Permissions {
+        if self.force_executable.is_empty() {
+            return permissions;
+        }
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return permissions,
+        };
+        if self
+            .force_executable
+            .iter()
+            .any(|pattern| glob_match(pattern, &name))
+        {
+            Permissions::Executable
+        } else {
+            permissions
+        }
+    }
+
+    fn report_progress(&self, path: &Path) {
+        if let Some(progress) = &self.progress {
+            progress(path);
+        }
+    }
+
+    fn check_file_size(&self, path: &Path) -> Result<(), SwhidError> {
+        if let Some(max) = self.max_file_size {
+            let size = fs::metadata(path)
+                .map_err(|e| SwhidError::io(path, e))?
+                .len();
+            if size > max {
+                return Err(SwhidError::FileTooLarge {
+                    path: path.to_path_buf(),
+                    size,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the content SWHID of a single file.
+    ///
+    /// When `path` is itself a symlink, this hashes the bytes of the link
+    /// target string (matching how every directory traversal in this crate
+    /// treats a symlink entry), not the bytes of whatever the link points
+    /// at — so this never follows the link, even a dangling one. Use
+    /// [`Content::from_file`] directly if reading through a symlink to hash
+    /// its target's content is genuinely what's wanted.
+    pub fn compute_file_swhid(&self, path: impl AsRef<Path>) -> Result<Swhid, SwhidError> {
+        let path = normalize_root(path.as_ref());
+        let is_symlink = fs::symlink_metadata(&path)
+            .map_err(|e| SwhidError::io(&path, e))?
+            .is_symlink();
+        if is_symlink {
+            return Ok(Content::from_symlink(&path)?.swhid());
+        }
+        self.check_file_size(&path)?;
+        let content = Content::from_file(&path)?;
+        Ok(content.swhid())
+    }
+
+    fn hash_entry(&self, path: &Path) -> Result<[u8; 20], SwhidError> {
+        self.report_progress(path);
+        let Some(cache) = &self.content_cache else {
+            return Ok(*self.compute_file_swhid(path)?.hash());
+        };
+        let metadata = fs::metadata(path).map_err(|e| SwhidError::io(path, e))?;
+        let mtime = metadata.modified().map_err(|e| SwhidError::io(path, e))?;
+        let len = metadata.len();
+        if let Some(hash) = cache.get(path, mtime, len) {
+            return Ok(hash);
+        }
+        let hash = *self.compute_file_swhid(path)?.hash();
+        cache.put(path, mtime, len, hash);
+        Ok(hash)
+    }
+
+    /// Recursively compute the directory SWHID rooted at `path`.
+    ///
+    /// A `.git` entry (at any depth, matching the unanchored-exclude
+    /// semantics above) is skipped by default, since git itself never
+    /// tracks it and a repository's committed tree SWHID shouldn't depend
+    /// on whether `.git` happens to sit inside the hashed path. Set
+    /// [`SwhidComputerBuilder::include_git_dir`] to hash it anyway. This is
+    /// independent of `.swhignore`/anchored excludes above: both apply on
+    /// top of the `.git` default, not instead of it.
+    pub fn compute_directory_swhid(&self, path: impl AsRef<Path>) -> Result<Swhid, SwhidError> {
+        let path = normalize_root(path.as_ref());
+        let directory = self.build_directory_with_excludes(&path, &mut |_, _| {})?;
+        Ok(directory.swhid())
+    }
+
+    /// Like [`SwhidComputer::compute_directory_swhid`], but also returns,
+    /// for every entry the `.swhignore`/anchored-exclude or `.git`-default
+    /// rules left out of the tree, the path and a description of the
+    /// pattern that excluded it (`"/.git"` for the built-in default, or the
+    /// literal `.swhignore` pattern text otherwise). Answers "why isn't my
+    /// file in this SWHID" in one call instead of re-deriving the exclude
+    /// rules by hand.
+    pub fn compute_directory_swhid_verbose(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Swhid, Vec<(PathBuf, String)>), SwhidError> {
+        let path = normalize_root(path.as_ref());
+        let mut excluded = Vec::new();
+        let directory = self.build_directory_with_excludes(&path, &mut |entry_path, pattern| {
+            excluded.push((entry_path.to_path_buf(), pattern.to_string()));
+        })?;
+        Ok((directory.swhid(), excluded))
+    }
+
+    fn build_directory_with_excludes(
+        &self,
+        path: &Path,
+        on_excluded: &mut dyn FnMut(&Path, &str),
+    ) -> Result<Directory, SwhidError> {
+        if !path.is_dir() {
+            return Err(SwhidError::NotADirectory(path.to_path_buf()));
+        }
+
+        let patterns = if self.swhignore {
+            let swhignore_path = path.join(".swhignore");
+            match fs::read_to_string(&swhignore_path) {
+                Ok(contents) => parse_swhignore(&contents),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(SwhidError::io(swhignore_path, e)),
+            }
+        } else {
+            Vec::new()
+        };
+
+        let on_excluded = std::cell::RefCell::new(on_excluded);
+        let should_skip = |entry_path: &Path| -> bool {
+            if !self.include_git_dir && entry_path.file_name().map(|n| n == ".git").unwrap_or(false) {
+                (on_excluded.borrow_mut())(entry_path, "/.git");
+                return true;
+            }
+            let matched = patterns.iter().find(|pattern| match pattern.strip_prefix('/') {
+                // A leading `/` anchors the pattern to the traversal root:
+                // match it against the path relative to `path`, not the
+                // bare name, so `exclude=/vendor/foo` only excludes that
+                // exact path at the root rather than every `foo` anywhere.
+                Some(anchored) => match entry_path.strip_prefix(path) {
+                    Ok(relative) => glob_match(anchored, &relative.to_string_lossy()),
+                    Err(_) => false,
+                },
+                None => match entry_path.file_name() {
+                    Some(name) => glob_match(pattern, &name.to_string_lossy()),
+                    None => false,
+                },
+            });
+            match matched {
+                Some(pattern) => {
+                    (on_excluded.borrow_mut())(entry_path, pattern);
+                    true
+                }
+                None => {
+                    // Files are reported by `hash_entry`; report directories here.
+                    if self.progress.is_some()
+                        && fs::symlink_metadata(entry_path).map(|m| m.is_dir()).unwrap_or(false)
+                    {
+                        self.report_progress(entry_path);
+                    }
+                    false
+                }
+            }
+        };
+
+        let permission_override =
+            |entry_path: &Path, permissions: Permissions| self.apply_force_executable(entry_path, permissions);
+
+        self.report_progress(path);
+        let directory = Directory::from_disk_filtered(
+            path,
+            &mut |p| self.hash_entry(p),
+            &should_skip,
+            &permission_override,
+        )?;
+        Ok(directory)
+    }
+
+    /// Like [`SwhidComputer::compute_directory_swhid`], but when
+    /// [`SwhidComputer::with_skip_unreadable`] is enabled, an entry that
+    /// can't be read due to a permission error is omitted from the tree
+    /// instead of aborting the whole computation. Returns the resulting
+    /// SWHID alongside every path that was skipped this way.
+    ///
+    /// Skipping entries necessarily changes the resulting SWHID from what
+    /// hashing the complete tree would produce, so a non-empty skip list
+    /// means the returned id isn't a faithful identifier for the whole
+    /// tree — only for the portion of it that could be read.
+    ///
+    /// Unlike [`SwhidComputer::compute_directory_swhid`], this applies
+    /// neither `.swhignore` patterns nor the default `.git` exclusion.
+    pub fn compute_directory_swhid_lenient(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Swhid, Vec<PathBuf>), SwhidError> {
+        let path = normalize_root(path.as_ref());
+        if !path.is_dir() {
+            return Err(SwhidError::NotADirectory(path));
+        }
+        let mut skipped = Vec::new();
+        let directory = self.build_directory_lenient(&path, &mut skipped)?;
+        Ok((directory.swhid(), skipped))
+    }
+
+    fn is_permission_denied(error: &SwhidError) -> bool {
+        matches!(error, SwhidError::Io { source, .. } if source.kind() == std::io::ErrorKind::PermissionDenied)
+    }
+
+    fn build_directory_lenient(
+        &self,
+        path: &Path,
+        skipped: &mut Vec<PathBuf>,
+    ) -> Result<Directory, SwhidError> {
+        let mut directory = Directory::new();
+        let read_dir = match fs::read_dir(path) {
+            Ok(read_dir) => read_dir,
+            Err(e) if self.skip_unreadable && e.kind() == std::io::ErrorKind::PermissionDenied => {
+                skipped.push(path.to_path_buf());
+                return Ok(directory);
+            }
+            Err(e) => return Err(SwhidError::io(path, e)),
+        };
+        let mut children: Vec<_> = read_dir
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| SwhidError::io(path, e))?;
+        children.sort_by_key(|e| e.file_name());
+        for child in children {
+            let child_path = child.path();
+            let metadata = match child.metadata() {
+                Ok(metadata) => metadata,
+                Err(e) if self.skip_unreadable && e.kind() == std::io::ErrorKind::PermissionDenied => {
+                    skipped.push(child_path);
+                    continue;
+                }
+                Err(e) => return Err(SwhidError::io(&child_path, e)),
+            };
+            let name = child.file_name().into_encoded_bytes();
+            let (permissions, target) = if metadata.is_dir() {
+                let subdir = self.build_directory_lenient(&child_path, skipped)?;
+                if skipped.last() == Some(&child_path) {
+                    // The subdirectory itself couldn't be listed: omit it.
+                    continue;
+                }
+                (Permissions::Directory, subdir.compute_hash())
+            } else {
+                let permissions = raw_mode_permissions(&metadata);
+                if permissions != Permissions::Symlink
+                    && crate::directory::is_unsupported_file_type(&metadata)
+                {
+                    return Err(SwhidError::UnsupportedFileType(child_path));
+                }
+                let permissions = if permissions == Permissions::Symlink {
+                    permissions
+                } else {
+                    self.apply_force_executable(&child_path, permissions)
+                };
+                let target = if permissions == Permissions::Symlink {
+                    hash_symlink_target(&child_path)?
+                } else {
+                    match self.compute_file_swhid(&child_path) {
+                        Ok(swhid) => *swhid.hash(),
+                        Err(e) if self.skip_unreadable && Self::is_permission_denied(&e) => {
+                            skipped.push(child_path);
+                            continue;
+                        }
+                        Err(e) => return Err(e),
+                    }
+                };
+                (permissions, target)
+            };
+            directory.add_entry(crate::directory::DirectoryEntry {
+                name,
+                permissions,
+                target: target.into(),
+            });
+        }
+        Ok(directory)
+    }
+
+    /// Recompute `root`'s directory SWHID after `changed` files were
+    /// modified, re-walking only `root` and the ancestor directories of
+    /// each changed path, and reusing every sibling subtree's hash from
+    /// `cache` instead of re-reading it from disk. `cache` is both input
+    /// and output: on the first call it should be empty (everything gets
+    /// walked and filled in, same cost as a full traversal), and on every
+    /// later call it holds the previous result so only the directories on
+    /// the path from `changed` to `root` get re-walked — turning a
+    /// file-watcher's repeated full-tree hash into O(depth) work per save
+    /// instead of O(tree).
+    ///
+    /// # Cache invariants
+    ///
+    /// `cache` maps each directory's absolute path to the [`Directory`]
+    /// this method last built for it. For the result to be correct:
+    /// - every path in `changed` must be a plain file or symlink directly
+    ///   under some directory already present in `cache` (or under `root`
+    ///   itself) — a *new* file, a *removed* file, or a renamed directory
+    ///   is a tree-shape change this method doesn't detect, since it only
+    ///   re-lists the directories it decides to walk, not every directory
+    ///   that's reachable;
+    /// - nothing outside the ancestor chain of `changed` changed on disk
+    ///   since the last call — an untracked edit elsewhere is silently
+    ///   missed, because its directory's cached hash is reused unchanged;
+    /// - `root` itself must not have moved or been replaced.
+    ///
+    /// When any of these don't hold, drop `cache` and call this (or
+    /// [`SwhidComputer::compute_directory_swhid`]) again with a fresh one.
+    /// This bypasses `.swhignore`/`.git`-exclusion and the
+    /// force-executable override: it's meant for the common case of a
+    /// plain working tree under fast-iterating file-watcher edits, not a
+    /// drop-in replacement for [`SwhidComputer::compute_directory_swhid`].
+    pub fn recompute_after_change(
+        &self,
+        root: impl AsRef<Path>,
+        changed: &[PathBuf],
+        cache: &mut std::collections::HashMap<PathBuf, Directory>,
+    ) -> Result<Swhid, SwhidError> {
+        let root = normalize_root(root.as_ref());
+
+        let mut dirty: Vec<PathBuf> = vec![root.clone()];
+        for changed_path in changed {
+            let mut dir = changed_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| root.clone());
+            loop {
+                if !dirty.contains(&dir) {
+                    dirty.push(dir.clone());
+                }
+                if dir == root {
+                    break;
+                }
+                match dir.parent() {
+                    Some(parent) => dir = parent.to_path_buf(),
+                    None => break,
+                }
+            }
+        }
+        // Deepest directories first, so a parent's re-hash can read its
+        // child's freshly updated entry out of `cache`.
+        dirty.sort_by_key(|path| std::cmp::Reverse(path.components().count()));
+
+        for dir_path in &dirty {
+            let directory = self.rebuild_directory_level(dir_path, cache)?;
+            cache.insert(dir_path.clone(), directory);
+        }
+
+        let root_directory = cache.get(&root).ok_or_else(|| {
+            SwhidError::NotADirectory(root.clone())
+        })?;
+        Ok(root_directory.swhid())
+    }
+
+    /// Re-list a single directory level (not recursively) for
+    /// [`SwhidComputer::recompute_after_change`], reusing each
+    /// subdirectory's hash from `cache` when present instead of
+    /// descending into it. A subdirectory not yet in `cache` (an off-chain
+    /// sibling of the changed path) is walked once via
+    /// [`SwhidComputer::build_directory_lenient`] and the result is
+    /// inserted into `cache`, so later calls reuse it instead of
+    /// re-walking the same untouched subtree every time — without this,
+    /// every directory with siblings degrades back to O(tree) per save.
+    fn rebuild_directory_level(
+        &self,
+        dir_path: &Path,
+        cache: &mut std::collections::HashMap<PathBuf, Directory>,
+    ) -> Result<Directory, SwhidError> {
+        let mut directory = Directory::new();
+        for entry in fs::read_dir(dir_path).map_err(|e| SwhidError::io(dir_path, e))? {
+            let entry = entry.map_err(|e| SwhidError::io(dir_path, e))?;
+            let entry_path = entry.path();
+            let metadata = entry.metadata().map_err(|e| SwhidError::io(&entry_path, e))?;
+            let name = entry.file_name().into_encoded_bytes();
+
+            let (permissions, target) = if metadata.is_dir() {
+                let hash = match cache.get(&entry_path) {
+                    Some(subdir) => subdir.compute_hash(),
+                    None => {
+                        let subdir = self.build_directory_lenient(&entry_path, &mut Vec::new())?;
+                        let hash = subdir.compute_hash();
+                        cache.insert(entry_path.clone(), subdir);
+                        hash
+                    }
+                };
+                (Permissions::Directory, hash)
+            } else {
+                let permissions = raw_mode_permissions(&metadata);
+                if permissions != Permissions::Symlink
+                    && crate::directory::is_unsupported_file_type(&metadata)
+                {
+                    return Err(SwhidError::UnsupportedFileType(entry_path));
+                }
+                let target = if permissions == Permissions::Symlink {
+                    hash_symlink_target(&entry_path)?
+                } else {
+                    self.hash_entry(&entry_path)?
+                };
+                (permissions, target)
+            };
+            directory.add_entry(crate::directory::DirectoryEntry {
+                name,
+                permissions,
+                target: target.into(),
+            });
+        }
+        Ok(directory)
+    }
+
+    /// Compute the directory SWHID of `std::env::current_dir()`, honoring
+    /// this computer's options.
+    pub fn compute_cwd_swhid(&self) -> Result<Swhid, SwhidError> {
+        let cwd = std::env::current_dir().map_err(|e| SwhidError::io(".", e))?;
+        self.compute_directory_swhid(&cwd)
+    }
+
+    /// Identify a git working tree: the directory SWHID of `repo_path`
+    /// itself, plus the revision SWHID of its `HEAD` commit when one can be
+    /// resolved from a `.git` directory alongside it.
+    ///
+    /// `HEAD` resolution only follows loose refs, `packed-refs`, and a
+    /// loose commit object for the resolved sha — it doesn't resolve a
+    /// `HEAD` commit that's only reachable inside a packfile. Whenever
+    /// `.git` metadata is missing, unreadable, or the commit can't be
+    /// resolved this way, `revision` comes back `None` rather than failing
+    /// the whole call, since the directory SWHID is still a meaningful
+    /// result on its own.
+    pub fn identify_checkout(
+        &self,
+        repo_path: impl AsRef<Path>,
+    ) -> Result<CheckoutSwhids, SwhidError> {
+        let repo_path = normalize_root(repo_path.as_ref());
+        let directory = self.compute_directory_swhid(&repo_path)?;
+        let revision = resolve_checkout_head_revision(&repo_path.join(".git"));
+        Ok(CheckoutSwhids { directory, revision })
+    }
+
+    /// Compute the SWHID of `relative_subpath` within the tree rooted at
+    /// `root`, without recomputing the whole tree's id.
+    pub fn compute_subtree_swhid(
+        &self,
+        root: impl AsRef<Path>,
+        relative_subpath: impl AsRef<Path>,
+    ) -> Result<Swhid, SwhidError> {
+        let root = normalize_root(root.as_ref());
+        let subpath = relative_subpath.as_ref();
+        let full_path = root.join(subpath);
+        if !full_path.exists() {
+            return Err(SwhidError::NotFound(full_path));
+        }
+        if !full_path.is_dir() {
+            return Err(SwhidError::NotADirectory(full_path));
+        }
+        self.compute_directory_swhid(&full_path)
+    }
+
+    /// Recursively walk `path`, returning every content and directory object
+    /// found, keyed by their absolute path — or by the path relative to
+    /// `path` if [`SwhidComputer::with_relative_paths`] is enabled.
+    pub fn traverse_directory_recursively(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<(PathBuf, TreeObject)>, SwhidError> {
+        Ok(self.traverse_directory_with_summary(path)?.0)
+    }
+
+    /// Like [`SwhidComputer::traverse_directory_recursively`], but also
+    /// returns a [`TraversalSummary`] aggregated across the whole tree (e.g.
+    /// total content bytes), computed during the same walk so callers don't
+    /// have to re-walk just to total it up.
+    pub fn traverse_directory_with_summary(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<(PathBuf, TreeObject)>, TraversalSummary), SwhidError> {
+        let path = normalize_root(path.as_ref());
+        let mut results = Vec::new();
+        let mut stats = TraversalStats::default();
+        let mut emit = |child_path: &Path, object: TreeObject| {
+            results.push((traversal_key(&path, child_path, self.relative_paths), object));
+        };
+        self.traverse_into(&path, &mut emit, &mut stats)?;
+        Ok((
+            results,
+            TraversalSummary {
+                total_content_size: stats.total_content_size,
+            },
+        ))
+    }
+
+    /// Like [`SwhidComputer::traverse_directory_recursively`], but also
+    /// returns a [`TraversalStats`] object-type breakdown (content,
+    /// directory and symlink counts, plus total content bytes) aggregated
+    /// across the whole tree, computed during the same walk so callers
+    /// don't need a second pass over the returned `Vec` to tally it up.
+    pub fn traverse_directory_recursively_with_stats(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<(PathBuf, TreeObject)>, TraversalStats), SwhidError> {
+        let path = normalize_root(path.as_ref());
+        let mut results = Vec::new();
+        let mut stats = TraversalStats::default();
+        let mut emit = |child_path: &Path, object: TreeObject| {
+            results.push((traversal_key(&path, child_path, self.relative_paths), object));
+        };
+        self.traverse_into(&path, &mut emit, &mut stats)?;
+        Ok((results, stats))
+    }
+
+    /// Like [`SwhidComputer::traverse_directory_recursively`], but streams
+    /// each object to `visitor` as it's computed (in post-order: a
+    /// directory's entries before the directory itself) instead of
+    /// collecting them into a `Vec`. Useful for huge trees where holding
+    /// every object in memory at once isn't practical, e.g. streaming
+    /// SWHIDs straight into a database.
+    pub fn walk<F>(&self, path: impl AsRef<Path>, mut visitor: F) -> Result<(), SwhidError>
+    where
+        F: FnMut(&Path, &mut TreeObject),
+    {
+        let path = normalize_root(path.as_ref());
+        let mut stats = TraversalStats::default();
+        let mut emit = |child_path: &Path, mut object: TreeObject| visitor(child_path, &mut object);
+        self.traverse_into(&path, &mut emit, &mut stats)?;
+        Ok(())
+    }
+
+    /// Recursively walk `root`, returning one [`LsTreeEntry`] per entry
+    /// (file, symlink or subdirectory) found anywhere in the tree, in the
+    /// same order `git ls-tree -r -t` would print them: each directory's
+    /// immediate children in git's tree-sort order, a subdirectory's own
+    /// row immediately followed by its expanded children, depth-first.
+    /// Meant for diffing straight against real `git ls-tree -r -t` output
+    /// on a git checkout to pinpoint exactly which entry a computed SWHID
+    /// first diverges at.
+    pub fn traverse_as_ls_tree(
+        &self,
+        root: impl AsRef<Path>,
+    ) -> Result<Vec<LsTreeEntry>, SwhidError> {
+        let root = normalize_root(root.as_ref());
+        let mut entries = Vec::new();
+        self.collect_ls_tree_entries(&root, "", &mut entries)?;
+        Ok(entries)
+    }
+
+    fn collect_ls_tree_entries(
+        &self,
+        dir_path: &Path,
+        relative_prefix: &str,
+        entries: &mut Vec<LsTreeEntry>,
+    ) -> Result<[u8; 20], SwhidError> {
+        let mut directory = Directory::new();
+        let read_dir = fs::read_dir(dir_path).map_err(|e| SwhidError::io(dir_path, e))?;
+        let mut children: Vec<_> = read_dir
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| SwhidError::io(dir_path, e))?;
+        children.sort_by_key(|e| {
+            crate::directory::git_tree_sort_key(
+                e.file_name().as_encoded_bytes(),
+                e.file_type().map(|t| t.is_dir()).unwrap_or(false),
+            )
+        });
+        for child in children {
+            let child_path = child.path();
+            let metadata = child
+                .metadata()
+                .map_err(|e| SwhidError::io(&child_path, e))?;
+            let name = child.file_name().into_encoded_bytes();
+            let relative_path = if relative_prefix.is_empty() {
+                String::from_utf8_lossy(&name).into_owned()
+            } else {
+                format!("{relative_prefix}/{}", String::from_utf8_lossy(&name))
+            };
+
+            let (permissions, target) = if metadata.is_dir() {
+                // Reserve this directory's row now so it prints before its
+                // children, then fill in its hash once the recursive call
+                // below has computed it.
+                let row_index = entries.len();
+                entries.push(LsTreeEntry {
+                    mode: "040000",
+                    kind: "tree",
+                    hash_hex: String::new(),
+                    path: relative_path.clone(),
+                });
+                let target = self.collect_ls_tree_entries(&child_path, &relative_path, entries)?;
+                entries[row_index].hash_hex = hex::encode(target);
+                (Permissions::Directory, target)
+            } else {
+                let permissions = raw_mode_permissions(&metadata);
+                if permissions != Permissions::Symlink
+                    && crate::directory::is_unsupported_file_type(&metadata)
+                {
+                    return Err(SwhidError::UnsupportedFileType(child_path));
+                }
+                let permissions = if permissions == Permissions::Symlink {
+                    permissions
+                } else {
+                    self.apply_force_executable(&child_path, permissions)
+                };
+                let target = if permissions == Permissions::Symlink {
+                    hash_symlink_target(&child_path)?
+                } else {
+                    *self.compute_file_swhid(&child_path)?.hash()
+                };
+                entries.push(LsTreeEntry {
+                    mode: permissions.git_mode(),
+                    kind: "blob",
+                    hash_hex: hex::encode(target),
+                    path: relative_path.clone(),
+                });
+                (permissions, target)
+            };
+            directory.add_entry(crate::directory::DirectoryEntry {
+                name,
+                permissions,
+                target: target.into(),
+            });
+        }
+        Ok(directory.compute_hash())
+    }
+
+    /// Like [`SwhidComputer::traverse_directory_recursively`], but collapses
+    /// objects that share a SWHID (e.g. identical files) down to a single
+    /// representative, keeping the first one encountered.
+    pub fn unique_objects(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<(Swhid, TreeObject)>, SwhidError> {
+        let mut seen = std::collections::HashSet::new();
+        let mut unique = Vec::new();
+        for (_, object) in self.traverse_directory_recursively(path)? {
+            if seen.insert(*object.swhid()) {
+                unique.push((*object.swhid(), object));
+            }
+        }
+        Ok(unique)
+    }
+
+    /// Walk `repo_path`'s working tree computing every content and
+    /// directory SWHID via [`SwhidComputer::unique_objects`], plus —
+    /// best-effort, via the same resolution [`SwhidComputer::identify_checkout`]
+    /// uses — the SWHID of the commit `HEAD` currently points at, and write
+    /// the whole set to `writer` as one hex SWHID per line, sorted and
+    /// deduplicated. Meant for snapshotting the full identifier set of a
+    /// release for archival in one call; see `examples/dump_repository_swhids.rs`.
+    pub fn dump_repository_swhids(
+        &self,
+        repo_path: impl AsRef<Path>,
+        writer: &mut impl std::io::Write,
+    ) -> Result<(), SwhidError> {
+        let repo_path = normalize_root(repo_path.as_ref());
+        let mut swhids: Vec<Swhid> = self
+            .unique_objects(&repo_path)?
+            .into_iter()
+            .map(|(swhid, _)| swhid)
+            .collect();
+        if let Some(head) = resolve_checkout_head_revision(&repo_path.join(".git")) {
+            swhids.push(head);
+        }
+        swhids.sort_by_key(Swhid::to_string);
+        swhids.dedup();
+        for swhid in &swhids {
+            writeln!(writer, "{swhid}").map_err(|e| SwhidError::io(&repo_path, e))?;
+        }
+        Ok(())
+    }
+
+    fn traverse_into<E>(
+        &self,
+        path: &Path,
+        emit: &mut E,
+        stats: &mut TraversalStats,
+    ) -> Result<[u8; 20], SwhidError>
+    where
+        E: FnMut(&Path, TreeObject),
+    {
+        self.report_progress(path);
+        if path.is_dir() {
+            let mut directory = Directory::new();
+            let read_dir = fs::read_dir(path).map_err(|e| SwhidError::io(path, e))?;
+            let mut children: Vec<_> = read_dir
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| SwhidError::io(path, e))?;
+            children.sort_by_key(|e| e.file_name());
+            for child in children {
+                let child_path = child.path();
+                let metadata = child
+                    .metadata()
+                    .map_err(|e| SwhidError::io(&child_path, e))?;
+                let name = child.file_name().into_encoded_bytes();
+                let (permissions, target) = if metadata.is_dir() {
+                    let target = self.traverse_into(&child_path, emit, stats)?;
+                    (Permissions::Directory, target)
+                } else {
+                    self.report_progress(&child_path);
+                    let permissions = raw_mode_permissions(&metadata);
+                    if permissions != Permissions::Symlink
+                        && crate::directory::is_unsupported_file_type(&metadata)
+                    {
+                        return Err(SwhidError::UnsupportedFileType(child_path));
+                    }
+                    let permissions = if permissions == Permissions::Symlink {
+                        permissions
+                    } else {
+                        self.apply_force_executable(&child_path, permissions)
+                    };
+                    let target = if permissions == Permissions::Symlink {
+                        // Hash the link's target path string directly rather
+                        // than following it, so dangling symlinks (whose
+                        // target doesn't exist) don't abort the traversal.
+                        let target = hash_symlink_target(&child_path)?;
+                        let swhid = Swhid::new(crate::swhid::ObjectType::Content, target);
+                        stats.symlink_count += 1;
+                        emit(&child_path, TreeObject::Content(swhid));
+                        target
+                    } else {
+                        stats.total_content_size += metadata.len();
+                        let swhid = self.compute_file_swhid(&child_path)?;
+                        let target = *swhid.hash();
+                        stats.content_count += 1;
+                        emit(&child_path, TreeObject::Content(swhid));
+                        target
+                    };
+                    (permissions, target)
+                };
+                directory.add_entry(crate::directory::DirectoryEntry {
+                    name,
+                    permissions,
+                    target: target.into(),
+                });
+            }
+            let swhid = directory.swhid();
+            stats.directory_count += 1;
+            emit(path, TreeObject::Directory(swhid));
+            Ok(*swhid.hash())
+        } else {
+            let is_symlink = match fs::symlink_metadata(path) {
+                Ok(metadata) => {
+                    let is_symlink = raw_mode_permissions(&metadata) == Permissions::Symlink;
+                    if !is_symlink {
+                        if crate::directory::is_unsupported_file_type(&metadata) {
+                            return Err(SwhidError::UnsupportedFileType(path.to_path_buf()));
+                        }
+                        stats.total_content_size += metadata.len();
+                    }
+                    is_symlink
+                }
+                Err(_) => false,
+            };
+            if is_symlink {
+                stats.symlink_count += 1;
+            } else {
+                stats.content_count += 1;
+            }
+            let swhid = self.compute_file_swhid(path)?;
+            emit(path, TreeObject::Content(swhid));
+            Ok(*swhid.hash())
+        }
+    }
+}
+
+/// A node in the in-memory tree built from tar entries by
+/// [`SwhidComputer::compute_tar_swhid`], before it's folded down into
+/// [`Directory`] objects bottom-up.
+#[cfg(feature = "tar")]
+enum TarNode {
+    File {
+        permissions: Permissions,
+        target: [u8; 20],
+    },
+    Dir(std::collections::BTreeMap<Vec<u8>, TarNode>),
+}
+
+#[cfg(feature = "tar")]
+fn tar_path_components(path: &Path) -> Vec<Vec<u8>> {
+    path.components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(name) => Some(name.to_os_string().into_encoded_bytes()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(feature = "tar")]
+fn tar_ensure_dir(root: &mut std::collections::BTreeMap<Vec<u8>, TarNode>, components: &[Vec<u8>]) {
+    let Some((head, rest)) = components.split_first() else {
+        return;
+    };
+    let entry = root
+        .entry(head.clone())
+        .or_insert_with(|| TarNode::Dir(std::collections::BTreeMap::new()));
+    if let TarNode::Dir(children) = entry {
+        tar_ensure_dir(children, rest);
+    }
+}
+
+#[cfg(feature = "tar")]
+fn tar_insert_file(
+    root: &mut std::collections::BTreeMap<Vec<u8>, TarNode>,
+    components: &[Vec<u8>],
+    node: TarNode,
+) {
+    let Some((head, rest)) = components.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        root.insert(head.clone(), node);
+        return;
+    }
+    let entry = root
+        .entry(head.clone())
+        .or_insert_with(|| TarNode::Dir(std::collections::BTreeMap::new()));
+    if let TarNode::Dir(children) = entry {
+        tar_insert_file(children, rest, node);
+    }
+}
+
+/// Remove the node at `components`, if present, from the in-progress tar
+/// tree — used by [`SwhidComputer::compute_layer_swhid`] to apply a named
+/// whiteout. A no-op if `components` doesn't resolve to anything (the
+/// common case: the whited-out path only exists in a lower layer this
+/// method never sees).
+#[cfg(feature = "tar")]
+fn tar_remove(root: &mut std::collections::BTreeMap<Vec<u8>, TarNode>, components: &[Vec<u8>]) {
+    let Some((head, rest)) = components.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        root.remove(head);
+        return;
+    }
+    if let Some(TarNode::Dir(children)) = root.get_mut(head) {
+        tar_remove(children, rest);
+    }
+}
+
+/// Classify a tar entry's base name as an AUFS-style whiteout marker:
+/// `Some(Some(name))` for a named whiteout `.wh.<name>` (deletes the
+/// sibling `<name>`), `Some(None)` for the opaque marker `.wh..wh..opq`
+/// (hides a lower layer's existing contents of this directory), or `None`
+/// for an ordinary entry.
+#[cfg(feature = "tar")]
+fn whiteout_target(name: &[u8]) -> Option<Option<Vec<u8>>> {
+    const OPAQUE: &[u8] = b".wh..wh..opq";
+    const PREFIX: &[u8] = b".wh.";
+    if name == OPAQUE {
+        Some(None)
+    } else {
+        name.strip_prefix(PREFIX).map(|rest| Some(rest.to_vec()))
+    }
+}
+
+#[cfg(feature = "tar")]
+fn tar_insert_entry<R: std::io::Read>(
+    root: &mut std::collections::BTreeMap<Vec<u8>, TarNode>,
+    entry: &mut tar::Entry<R>,
+    components: &[Vec<u8>],
+    tar_err: &dyn Fn(std::io::Error) -> SwhidError,
+) -> Result<(), SwhidError> {
+    use std::io::Read as _;
+
+    match entry.header().entry_type() {
+        tar::EntryType::Directory => tar_ensure_dir(root, components),
+        tar::EntryType::Symlink => {
+            let link_name = entry
+                .link_name()
+                .map_err(tar_err)?
+                .ok_or_else(|| {
+                    SwhidError::InvalidGitObject("symlink entry has no link target".into())
+                })?
+                .into_owned();
+            let target = crate::hash::hash_git_object(
+                "blob",
+                link_name.into_os_string().into_encoded_bytes().as_slice(),
+            );
+            tar_insert_file(
+                root,
+                components,
+                TarNode::File {
+                    permissions: Permissions::Symlink,
+                    target,
+                },
+            );
+        }
+        tar::EntryType::Regular | tar::EntryType::Continuous => {
+            let mode = entry.header().mode().unwrap_or(0o644);
+            let permissions = if mode & 0o111 != 0 {
+                Permissions::Executable
+            } else {
+                Permissions::Regular
+            };
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map_err(tar_err)?;
+            let target = crate::hash::hash_git_object("blob", &data);
+            tar_insert_file(root, components, TarNode::File { permissions, target });
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tar")]
+fn tar_build_directory(children: &std::collections::BTreeMap<Vec<u8>, TarNode>) -> Directory {
+    let mut directory = Directory::new();
+    for (name, node) in children {
+        let (permissions, target) = match node {
+            TarNode::File {
+                permissions,
+                target,
+            } => (*permissions, *target),
+            TarNode::Dir(children) => {
+                let subdir = tar_build_directory(children);
+                (Permissions::Directory, subdir.compute_hash())
+            }
+        };
+        directory.add_entry(crate::directory::DirectoryEntry {
+            name: name.clone(),
+            permissions,
+            target: target.into(),
+        });
+    }
+    directory
+}
+
+impl SwhidComputer {
+    /// Compute the top-level directory SWHID of a tar archive without
+    /// extracting it to disk: walks every entry, builds the directory tree
+    /// in memory (synthesizing intermediate directories from file paths the
+    /// same way git does), and hashes it with the usual git tree sorting.
+    /// Regular files, directories and symlinks are handled; other entry
+    /// types (hardlinks, devices, ...) are skipped.
+    #[cfg(feature = "tar")]
+    pub fn compute_tar_swhid<R: std::io::Read>(&self, reader: R) -> Result<Swhid, SwhidError> {
+        let tar_err = |e: std::io::Error| SwhidError::io(PathBuf::from("<tar archive>"), e);
+
+        let mut root = std::collections::BTreeMap::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(tar_err)? {
+            let mut entry = entry.map_err(tar_err)?;
+            let path = entry.path().map_err(tar_err)?.into_owned();
+            let components = tar_path_components(&path);
+            if components.is_empty() {
+                continue;
+            }
+            tar_insert_entry(&mut root, &mut entry, &components, &tar_err)?;
+        }
+
+        Ok(tar_build_directory(&root).swhid())
+    }
+
+    /// Compute the effective top-level directory SWHID of a single OCI/
+    /// docker layer tarball, on top of [`SwhidComputer::compute_tar_swhid`]'s
+    /// generic tar walk.
+    ///
+    /// Handles AUFS-style whiteout files the way an image runtime applying
+    /// this layer over a lower one would: an entry named `.wh.<name>` is a
+    /// deletion marker for the sibling `<name>` — both the marker and
+    /// `<name>` (if this same layer happens to also contain it, which only
+    /// arises from layer squashing) are omitted from the resulting tree.
+    /// An entry named `.wh..wh..opq` marks its directory *opaque*, meaning
+    /// a lower layer's existing contents of that directory should be
+    /// hidden once layers are stacked — but since this method only sees one
+    /// layer, there's no lower layer to hide, so the opaque marker is
+    /// simply omitted like any other whiteout and has no further effect
+    /// here. Building the merged tree across several stacked layers is out
+    /// of scope.
+    #[cfg(feature = "tar")]
+    pub fn compute_layer_swhid<R: std::io::Read>(&self, reader: R) -> Result<Swhid, SwhidError> {
+        let tar_err = |e: std::io::Error| SwhidError::io(PathBuf::from("<layer archive>"), e);
+
+        let mut root = std::collections::BTreeMap::new();
+        let mut whiteouts = Vec::new();
+        let mut archive = tar::Archive::new(reader);
+        for entry in archive.entries().map_err(tar_err)? {
+            let mut entry = entry.map_err(tar_err)?;
+            let path = entry.path().map_err(tar_err)?.into_owned();
+            let components = tar_path_components(&path);
+            if components.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = components.last() {
+                match whiteout_target(name) {
+                    Some(Some(target_name)) => {
+                        let mut target_path = components[..components.len() - 1].to_vec();
+                        target_path.push(target_name);
+                        whiteouts.push(target_path);
+                        continue;
+                    }
+                    Some(None) => continue,
+                    None => {}
+                }
+            }
+
+            tar_insert_entry(&mut root, &mut entry, &components, &tar_err)?;
+        }
+        // Applied after the walk, so a whited-out `<name>` is dropped
+        // whether it comes before or after its marker in the archive.
+        for target_path in &whiteouts {
+            tar_remove(&mut root, target_path);
+        }
+
+        Ok(tar_build_directory(&root).swhid())
+    }
+}
+
+/// The result of [`SwhidComputer::identify_checkout`]: the working tree's
+/// directory SWHID, plus its `HEAD` commit's revision SWHID when one could
+/// be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckoutSwhids {
+    pub directory: Swhid,
+    pub revision: Option<Swhid>,
+}
+
+/// Resolve `git_dir/HEAD` down to a commit sha: either directly (detached
+/// HEAD) or by following a `ref: refs/heads/...` line to a loose ref file
+/// or, failing that, a `packed-refs` entry. Returns `None` at any step that
+/// doesn't pan out, since `HEAD` resolution here is best-effort.
+fn resolve_head_sha(git_dir: &Path) -> Option<[u8; 20]> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let sha_hex = match head.strip_prefix("ref: ") {
+        Some(ref_path) => {
+            let loose_ref = git_dir.join(ref_path.trim());
+            match fs::read_to_string(&loose_ref) {
+                Ok(contents) => contents.trim().to_string(),
+                Err(_) => read_packed_ref(git_dir, ref_path.trim())?,
+            }
+        }
+        None => head.to_string(),
+    };
+    let bytes = hex::decode(sha_hex.trim()).ok()?;
+    bytes.try_into().ok()
+}
+
+/// Look up `ref_name` (e.g. `refs/heads/main`) in `git_dir/packed-refs`,
+/// which git falls back to once a ref's loose file has been packed away.
+fn read_packed_ref(git_dir: &Path, ref_name: &str) -> Option<String> {
+    let packed = fs::read_to_string(git_dir.join("packed-refs")).ok()?;
+    for line in packed.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let (sha, name) = line.split_once(' ')?;
+        if name == ref_name {
+            return Some(sha.to_string());
+        }
+    }
+    None
+}
+
+/// Inflate the loose object stored at `git_dir/objects/<sha[0:2]>/<sha[2:]>`
+/// and return its body (everything after the `<type> <len>\0` header), if
+/// it exists and decodes to a `commit` object. Packed (non-loose) commits
+/// aren't resolved here.
+fn read_loose_commit_body(git_dir: &Path, sha: &[u8; 20]) -> Option<Vec<u8>> {
+    let sha_hex = hex::encode(sha);
+    let object_path = git_dir
+        .join("objects")
+        .join(&sha_hex[0..2])
+        .join(&sha_hex[2..]);
+    let compressed = fs::read(object_path).ok()?;
+    let mut decoder = flate2::read::ZlibDecoder::new(&compressed[..]);
+    let mut decoded = Vec::new();
+    std::io::Read::read_to_end(&mut decoder, &mut decoded).ok()?;
+    let nul = decoded.iter().position(|&b| b == 0)?;
+    let header = std::str::from_utf8(&decoded[..nul]).ok()?;
+    if !header.starts_with("commit ") {
+        return None;
+    }
+    Some(decoded[nul + 1..].to_vec())
+}
+
+/// Resolve `git_dir`'s `HEAD` commit to a revision SWHID, returning `None`
+/// if `git_dir` isn't a git directory, `HEAD` can't be resolved, or the
+/// resolved commit isn't available as a loose object.
+fn resolve_checkout_head_revision(git_dir: &Path) -> Option<Swhid> {
+    let sha = resolve_head_sha(git_dir)?;
+    let body = read_loose_commit_body(git_dir, &sha)?;
+    Some(Swhid::new(
+        crate::swhid::ObjectType::Revision,
+        crate::hash::hash_git_object("commit", &body),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn tree_object_display_names_its_kind() {
+        let swhid = crate::content::Content::from_data(b"x".to_vec()).swhid();
+        let content = TreeObject::Content(swhid);
+        assert_eq!(content.to_string(), format!("content({swhid})"));
+
+        let directory = TreeObject::Directory(swhid);
+        assert_eq!(directory.to_string(), format!("directory({swhid})"));
+    }
+
+    #[test]
+    fn identified_object_dedupes_a_hashset_by_swhid() {
+        let swhid = crate::content::Content::from_data(b"x".to_vec()).swhid();
+        let one = IdentifiedObject::new(TreeObject::Content(swhid));
+        let other = IdentifiedObject::new(TreeObject::Content(swhid));
+        assert_eq!(one, other);
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(one);
+        set.insert(other);
+        assert_eq!(set.len(), 1);
+
+        let different_swhid = crate::content::Content::from_data(b"y".to_vec()).swhid();
+        set.insert(IdentifiedObject::new(TreeObject::Content(different_swhid)));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn traverse_directory_with_summary_totals_regular_file_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), b"world!").unwrap();
+        std::os::unix::fs::symlink("a.txt", dir.path().join("link")).unwrap();
+
+        let computer = SwhidComputer::new();
+        let (objects, summary) = computer.traverse_directory_with_summary(dir.path()).unwrap();
+        assert_eq!(summary.total_content_size, 5 + 6);
+        assert_eq!(
+            objects,
+            computer.traverse_directory_recursively(dir.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn relative_paths_strips_the_root_prefix_and_keys_the_root_itself_as_dot() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), b"world!").unwrap();
+
+        let computer = SwhidComputer::builder().relative_paths(true).build();
+        let objects = computer.traverse_directory_recursively(dir.path()).unwrap();
+        let paths: std::collections::BTreeSet<_> =
+            objects.iter().map(|(path, _)| path.clone()).collect();
+
+        assert_eq!(
+            paths,
+            std::collections::BTreeSet::from([
+                PathBuf::from("a.txt"),
+                PathBuf::from("sub"),
+                PathBuf::from("sub/b.txt"),
+                PathBuf::from("."),
+            ])
+        );
+        assert!(paths.iter().all(|p| !p.is_absolute()));
+
+        #[allow(deprecated)]
+        let via_deprecated_setter = SwhidComputer::new().with_relative_paths(true);
+        assert_eq!(
+            via_deprecated_setter
+                .traverse_directory_recursively(dir.path())
+                .unwrap(),
+            objects
+        );
+    }
+
+    #[test]
+    fn relative_paths_defaults_to_absolute_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        let objects = computer.traverse_directory_recursively(dir.path()).unwrap();
+        assert!(objects.iter().all(|(path, _)| path.is_absolute()));
+    }
+
+    #[test]
+    fn traverse_as_ls_tree_matches_git_ls_tree_modes_kinds_and_order() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), b"world!").unwrap();
+
+        let computer = SwhidComputer::new();
+        let entries = computer.traverse_as_ls_tree(dir.path()).unwrap();
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        // Git prints a directory's own row where it sorts among its
+        // siblings, then its expanded children immediately after.
+        assert_eq!(paths, vec!["a.txt", "sub", "sub/b.txt"]);
+
+        let sub = entries.iter().find(|e| e.path == "sub").unwrap();
+        assert_eq!(sub.mode, "040000");
+        assert_eq!(sub.kind, "tree");
+
+        let a = entries.iter().find(|e| e.path == "a.txt").unwrap();
+        assert_eq!(a.mode, "100644");
+        assert_eq!(a.kind, "blob");
+        assert_eq!(
+            a.hash_hex,
+            hex::encode(*computer.compute_file_swhid(dir.path().join("a.txt")).unwrap().hash())
+        );
+
+        assert_eq!(
+            sub.hash_hex,
+            hex::encode(*computer.compute_directory_swhid(dir.path().join("sub")).unwrap().hash())
+        );
+    }
+
+    #[test]
+    fn traverse_directory_recursively_with_stats_breaks_down_object_types() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), b"world!").unwrap();
+        std::os::unix::fs::symlink("a.txt", dir.path().join("link")).unwrap();
+
+        let computer = SwhidComputer::new();
+        let (objects, stats) = computer
+            .traverse_directory_recursively_with_stats(dir.path())
+            .unwrap();
+        assert_eq!(stats.content_count, 2);
+        assert_eq!(stats.directory_count, 2); // `sub` plus the root itself
+        assert_eq!(stats.symlink_count, 1);
+        assert_eq!(stats.excluded_count, 0);
+        assert_eq!(stats.total_content_size, 5 + 6);
+        assert_eq!(
+            objects,
+            computer.traverse_directory_recursively(dir.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn in_memory_content_cache_hits_only_while_mtime_and_len_match() {
+        let cache = InMemoryContentCache::new();
+        let path = Path::new("some/file.txt");
+        let mtime = std::time::SystemTime::UNIX_EPOCH;
+        assert_eq!(cache.get(path, mtime, 5), None);
+
+        cache.put(path, mtime, 5, [1u8; 20]);
+        assert_eq!(cache.get(path, mtime, 5), Some([1u8; 20]));
+        assert_eq!(cache.get(path, mtime, 6), None, "length changed");
+        assert_eq!(
+            cache.get(path, mtime + std::time::Duration::from_secs(1), 5),
+            None,
+            "mtime changed"
+        );
+    }
+
+    #[test]
+    fn content_cache_is_consulted_by_directory_hashing_and_can_go_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, b"hello").unwrap();
+        let metadata = fs::metadata(&file).unwrap();
+
+        // Prime the cache with a deliberately wrong hash for the current
+        // mtime/len, proving compute_directory_swhid takes the cached value
+        // rather than re-reading the file.
+        let stale_hash = [0xABu8; 20];
+        let cache = InMemoryContentCache::new();
+        cache.put(&file, metadata.modified().unwrap(), metadata.len(), stale_hash);
+
+        let cached_computer = SwhidComputer::builder().content_cache(cache).build();
+        let uncached_computer = SwhidComputer::new();
+        assert_ne!(
+            cached_computer.compute_directory_swhid(dir.path()).unwrap(),
+            uncached_computer.compute_directory_swhid(dir.path()).unwrap(),
+            "a populated cache entry should override the real file content"
+        );
+    }
+
+    #[test]
+    fn walk_visits_the_same_objects_as_traverse_directory_recursively() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/b.txt"), b"world!").unwrap();
+
+        let computer = SwhidComputer::new();
+        let mut visited = Vec::new();
+        computer
+            .walk(dir.path(), |path, object| {
+                visited.push((path.to_path_buf(), object.clone()))
+            })
+            .unwrap();
+
+        let expected = computer.traverse_directory_recursively(dir.path()).unwrap();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn subtree_swhid_matches_root_swhid_of_same_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("file.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        let subtree = computer.compute_subtree_swhid(dir.path(), "a/b").unwrap();
+        let direct = computer.compute_directory_swhid(&nested).unwrap();
+        assert_eq!(subtree, direct);
+    }
+
+    #[test]
+    fn subtree_swhid_rejects_missing_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let computer = SwhidComputer::new();
+        assert!(computer
+            .compute_subtree_swhid(dir.path(), "does/not/exist")
+            .is_err());
+    }
+
+    /// Restores the previous working directory when dropped, so a failing
+    /// assertion in one test can't leave later tests running from the
+    /// wrong cwd.
+    struct CwdGuard {
+        previous: PathBuf,
+    }
+
+    impl CwdGuard {
+        fn enter(path: &Path) -> Self {
+            let previous = std::env::current_dir().unwrap();
+            std::env::set_current_dir(path).unwrap();
+            CwdGuard { previous }
+        }
+    }
+
+    impl Drop for CwdGuard {
+        fn drop(&mut self) {
+            let _ = std::env::set_current_dir(&self.previous);
+        }
+    }
+
+    #[test]
+    fn cwd_swhid_matches_explicit_path_swhid() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+        let computer = SwhidComputer::new();
+        let expected = computer.compute_directory_swhid(dir.path()).unwrap();
+
+        let _guard = CwdGuard::enter(dir.path());
+        assert_eq!(computer.compute_cwd_swhid().unwrap(), expected);
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// Write a minimal loose commit object into `git_dir/objects/...` and
+    /// point `refs/heads/main` (via `HEAD`) at it, returning the commit
+    /// body so tests can compute the expected revision SWHID.
+    fn write_fake_git_dir(git_dir: &Path) -> Vec<u8> {
+        let body = b"tree 0101010101010101010101010101010101010101\n\
+author Jane Dev <jane@example.com> 1700000000 +0000\n\
+committer Jane Dev <jane@example.com> 1700000000 +0000\n\
+\n\
+msg\n"
+            .to_vec();
+        let sha = crate::hash::hash_git_object("commit", &body);
+        let sha_hex = hex::encode(sha);
+
+        let mut header_and_body = format!("commit {}\0", body.len()).into_bytes();
+        header_and_body.extend_from_slice(&body);
+
+        fs::create_dir_all(git_dir.join("objects").join(&sha_hex[0..2])).unwrap();
+        fs::write(
+            git_dir.join("objects").join(&sha_hex[0..2]).join(&sha_hex[2..]),
+            deflate(&header_and_body),
+        )
+        .unwrap();
+
+        fs::create_dir_all(git_dir.join("refs/heads")).unwrap();
+        fs::write(git_dir.join("refs/heads/main"), format!("{sha_hex}\n")).unwrap();
+        fs::write(git_dir.join("HEAD"), b"ref: refs/heads/main\n").unwrap();
+
+        body
+    }
+
+    #[test]
+    fn identify_checkout_resolves_the_head_commit_from_a_loose_ref_and_object() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+        let body = write_fake_git_dir(&dir.path().join(".git"));
+
+        let computer = SwhidComputer::new();
+        let result = computer.identify_checkout(dir.path()).unwrap();
+        assert_eq!(
+            result.directory,
+            computer.compute_directory_swhid(dir.path()).unwrap()
+        );
+        assert_eq!(
+            result.revision,
+            Some(Swhid::new(
+                crate::swhid::ObjectType::Revision,
+                crate::hash::hash_git_object("commit", &body)
+            ))
+        );
+    }
+
+    #[test]
+    fn identify_checkout_resolves_head_via_packed_refs_when_the_loose_ref_is_gone() {
+        let dir = tempfile::tempdir().unwrap();
+        let git_dir = dir.path().join(".git");
+        let body = write_fake_git_dir(&git_dir);
+        let sha_hex = hex::encode(crate::hash::hash_git_object("commit", &body));
+        fs::remove_file(git_dir.join("refs/heads/main")).unwrap();
+        fs::write(
+            git_dir.join("packed-refs"),
+            format!("# pack-refs with: peeled fully-peeled sorted\n{sha_hex} refs/heads/main\n"),
+        )
+        .unwrap();
+
+        let computer = SwhidComputer::new();
+        let result = computer.identify_checkout(dir.path()).unwrap();
+        assert!(result.revision.is_some());
+    }
+
+    #[test]
+    fn identify_checkout_returns_directory_only_when_there_is_no_git_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        let result = computer.identify_checkout(dir.path()).unwrap();
+        assert_eq!(
+            result.directory,
+            computer.compute_directory_swhid(dir.path()).unwrap()
+        );
+        assert_eq!(result.revision, None);
+    }
+
+    #[test]
+    fn dump_repository_swhids_writes_every_unique_swhid_plus_head_sorted() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"same content").unwrap();
+        fs::write(dir.path().join("b.txt"), b"same content").unwrap();
+        fs::write(dir.path().join("c.txt"), b"different").unwrap();
+        let body = write_fake_git_dir(&dir.path().join(".git"));
+        let head_swhid = Swhid::new(
+            crate::swhid::ObjectType::Revision,
+            crate::hash::hash_git_object("commit", &body),
+        );
+
+        let computer = SwhidComputer::new();
+        let mut output = Vec::new();
+        computer.dump_repository_swhids(dir.path(), &mut output).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+
+        let mut expected: Vec<String> = computer
+            .unique_objects(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|(swhid, _)| swhid.to_string())
+            .collect();
+        expected.push(head_swhid.to_string());
+        expected.sort();
+        expected.dedup();
+
+        assert_eq!(lines, expected);
+        let mut sorted = lines.clone();
+        sorted.sort();
+        assert_eq!(lines, sorted, "output must already be sorted");
+    }
+
+    #[test]
+    fn dump_repository_swhids_omits_head_when_there_is_no_git_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("only.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        let mut output = Vec::new();
+        computer.dump_repository_swhids(dir.path(), &mut output).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&output).unwrap().lines().collect();
+
+        // just the file content and the root directory, no `rev` line.
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| !line.contains(":rev:")));
+    }
+
+    #[test]
+    fn unique_objects_dedupes_identical_files_by_swhid() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"same content").unwrap();
+        fs::write(dir.path().join("b.txt"), b"same content").unwrap();
+        fs::write(dir.path().join("c.txt"), b"different").unwrap();
+
+        let computer = SwhidComputer::new();
+        let all = computer.traverse_directory_recursively(dir.path()).unwrap();
+        let unique = computer.unique_objects(dir.path()).unwrap();
+
+        // a.txt, b.txt, c.txt and the root directory = 4 raw entries, but
+        // a.txt/b.txt collapse to one content SWHID.
+        assert_eq!(all.len(), 4);
+        assert_eq!(unique.len(), 3);
+
+        let swhids: std::collections::HashSet<_> = unique.iter().map(|(s, _)| *s).collect();
+        assert_eq!(swhids.len(), unique.len());
+    }
+
+    #[test]
+    fn max_file_size_guard_rejects_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("big.txt");
+        fs::write(&file, vec![0u8; 1024]).unwrap();
+
+        let computer = SwhidComputer::builder().max_file_size(10).build();
+        match computer.compute_file_swhid(&file) {
+            Err(SwhidError::FileTooLarge { size, .. }) => assert_eq!(size, 1024),
+            other => panic!("expected FileTooLarge, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn progress_callback_fires_once_per_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), b"a").unwrap();
+        fs::write(dir.path().join("b.txt"), b"b").unwrap();
+
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+        let computer = SwhidComputer::builder()
+            .progress(move |path| {
+                seen_clone.lock().unwrap().push(path.to_path_buf());
+            })
+            .build();
+        computer.traverse_directory_recursively(dir.path()).unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3); // root dir + 2 files
+    }
+
+    #[test]
+    fn swhignore_excludes_matching_files_from_the_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), b"keep").unwrap();
+        fs::write(dir.path().join("drop.bak"), b"drop").unwrap();
+        fs::write(dir.path().join(".swhignore"), b"*.bak\n# comment\n").unwrap();
+
+        let without_bak = tempfile::tempdir().unwrap();
+        fs::write(without_bak.path().join("keep.txt"), b"keep").unwrap();
+        fs::write(without_bak.path().join(".swhignore"), b"*.bak\n# comment\n").unwrap();
+
+        let computer = SwhidComputer::builder().swhignore(true).build();
+        let with_ignore = computer.compute_directory_swhid(dir.path()).unwrap();
+        let expected = computer.compute_directory_swhid(without_bak.path()).unwrap();
+        assert_eq!(with_ignore, expected);
+    }
+
+    #[test]
+    fn unanchored_swhignore_pattern_excludes_a_matching_name_at_any_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("vendor/src")).unwrap();
+        fs::write(dir.path().join("vendor/src/lib.rs"), b"lib").unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), b"main").unwrap();
+        fs::write(dir.path().join(".swhignore"), b"src\n").unwrap();
+
+        let without_any_src = tempfile::tempdir().unwrap();
+        fs::create_dir_all(without_any_src.path().join("vendor")).unwrap();
+        fs::write(without_any_src.path().join(".swhignore"), b"src\n").unwrap();
+
+        let computer = SwhidComputer::builder().swhignore(true).build();
+        let with_ignore = computer.compute_directory_swhid(dir.path()).unwrap();
+        let expected = computer
+            .compute_directory_swhid(without_any_src.path())
+            .unwrap();
+        assert_eq!(with_ignore, expected);
+    }
+
+    #[test]
+    fn anchored_swhignore_pattern_excludes_only_that_path_from_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("vendor/src")).unwrap();
+        fs::write(dir.path().join("vendor/src/lib.rs"), b"lib").unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), b"main").unwrap();
+        fs::write(dir.path().join(".swhignore"), b"/src\n").unwrap();
+
+        let without_root_src = tempfile::tempdir().unwrap();
+        fs::create_dir_all(without_root_src.path().join("vendor/src")).unwrap();
+        fs::write(without_root_src.path().join("vendor/src/lib.rs"), b"lib").unwrap();
+        fs::write(without_root_src.path().join(".swhignore"), b"/src\n").unwrap();
+
+        let computer = SwhidComputer::builder().swhignore(true).build();
+        let with_ignore = computer.compute_directory_swhid(dir.path()).unwrap();
+        let expected = computer
+            .compute_directory_swhid(without_root_src.path())
+            .unwrap();
+        // The anchored pattern drops the root-level `src`, but `vendor/src`
+        // (which isn't at the root) survives.
+        assert_eq!(with_ignore, expected);
+
+        let without_vendor_src_either = tempfile::tempdir().unwrap();
+        fs::write(without_vendor_src_either.path().join(".swhignore"), b"/src\n").unwrap();
+        let missing_vendor_src = computer
+            .compute_directory_swhid(without_vendor_src_either.path())
+            .unwrap();
+        assert_ne!(with_ignore, missing_vendor_src);
+    }
+
+    #[test]
+    fn git_dir_is_excluded_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/HEAD"), b"ref: refs/heads/main\n").unwrap();
+
+        let without_git = tempfile::tempdir().unwrap();
+        fs::write(without_git.path().join("file.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        assert_eq!(
+            computer.compute_directory_swhid(dir.path()).unwrap(),
+            computer.compute_directory_swhid(without_git.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn compute_directory_swhid_verbose_matches_the_normal_swhid_and_lists_why_entries_were_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), b"keep").unwrap();
+        fs::create_dir_all(dir.path().join("vendor")).unwrap();
+        fs::write(dir.path().join("vendor/lib.rs"), b"lib").unwrap();
+        fs::write(dir.path().join(".swhignore"), b"/vendor\n").unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/HEAD"), b"ref: refs/heads/main\n").unwrap();
+
+        let computer = SwhidComputer::builder().swhignore(true).build();
+        let (verbose_swhid, excluded) = computer.compute_directory_swhid_verbose(dir.path()).unwrap();
+        assert_eq!(verbose_swhid, computer.compute_directory_swhid(dir.path()).unwrap());
+
+        let git_entry = excluded
+            .iter()
+            .find(|(path, _)| path == &dir.path().join(".git"))
+            .expect(".git should be reported as excluded");
+        assert_eq!(git_entry.1, "/.git");
+
+        let vendor_entry = excluded
+            .iter()
+            .find(|(path, _)| path == &dir.path().join("vendor"))
+            .expect("vendor should be reported as excluded");
+        assert_eq!(vendor_entry.1, "/vendor");
+    }
+
+    #[test]
+    fn include_git_dir_opts_back_into_hashing_it() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/HEAD"), b"ref: refs/heads/main\n").unwrap();
+
+        let excluding = SwhidComputer::new();
+        let including = SwhidComputer::builder().include_git_dir(true).build();
+        assert_ne!(
+            excluding.compute_directory_swhid(dir.path()).unwrap(),
+            including.compute_directory_swhid(dir.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn trailing_slash_on_root_does_not_change_the_swhid() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        let without_slash = computer.compute_directory_swhid(dir.path()).unwrap();
+        let mut with_slash = dir.path().as_os_str().to_os_string();
+        with_slash.push(std::path::MAIN_SEPARATOR.to_string());
+        let with_slash = computer
+            .compute_directory_swhid(PathBuf::from(with_slash))
+            .unwrap();
+        assert_eq!(without_slash, with_slash);
+    }
+
+    #[test]
+    fn force_executable_overrides_mode_for_matching_names() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("run.sh"), b"#!/bin/sh\necho hi\n").unwrap();
+
+        let plain = SwhidComputer::new().compute_directory_swhid(dir.path()).unwrap();
+        let forced = SwhidComputer::builder()
+            .force_executable(["*.sh"])
+            .build()
+            .compute_directory_swhid(dir.path())
+            .unwrap();
+        // Same content, but the manifest mode for run.sh differs (100644 vs
+        // 100755), so the directory SWHID must differ too.
+        assert_ne!(plain, forced);
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn compute_tar_swhid_matches_the_extracted_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+        fs::write(dir.path().join("sub/nested.txt"), b"nested").unwrap();
+
+        let mut tar_bytes = Vec::new();
+        {
+            let mut builder = tar::Builder::new(&mut tar_bytes);
+            builder.append_dir_all(".", dir.path()).unwrap();
+            builder.finish().unwrap();
+        }
+
+        let computer = SwhidComputer::new();
+        let from_tree = computer.compute_directory_swhid(dir.path()).unwrap();
+        let from_tar = computer.compute_tar_swhid(tar_bytes.as_slice()).unwrap();
+        assert_eq!(from_tree, from_tar);
+    }
+
+    #[cfg(feature = "tar")]
+    fn append_tar_file(builder: &mut tar::Builder<&mut Vec<u8>>, path: &str, data: &[u8]) {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, data).unwrap();
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn compute_layer_swhid_omits_a_named_whiteout_and_its_target() {
+        let mut tar_bytes = Vec::new();
+        {
+            let mut builder = tar::Builder::new(&mut tar_bytes);
+            append_tar_file(&mut builder, "kept.txt", b"still here");
+            append_tar_file(&mut builder, "removed.txt", b"deleted by a lower layer");
+            append_tar_file(&mut builder, ".wh.removed.txt", b"");
+            builder.finish().unwrap();
+        }
+
+        let computer = SwhidComputer::new();
+        let layer_swhid = computer.compute_layer_swhid(tar_bytes.as_slice()).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("kept.txt"), b"still here").unwrap();
+        let expected = computer.compute_directory_swhid(dir.path()).unwrap();
+
+        assert_eq!(layer_swhid, expected);
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn compute_layer_swhid_omits_the_opaque_whiteout_marker_itself() {
+        let mut tar_bytes = Vec::new();
+        {
+            let mut builder = tar::Builder::new(&mut tar_bytes);
+            append_tar_file(&mut builder, "sub/.wh..wh..opq", b"");
+            append_tar_file(&mut builder, "sub/new.txt", b"added by this layer");
+            builder.finish().unwrap();
+        }
+
+        let computer = SwhidComputer::new();
+        let layer_swhid = computer.compute_layer_swhid(tar_bytes.as_slice()).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/new.txt"), b"added by this layer").unwrap();
+        let expected = computer.compute_directory_swhid(dir.path()).unwrap();
+
+        assert_eq!(layer_swhid, expected);
+    }
+
+    #[cfg(feature = "tar")]
+    #[test]
+    fn compute_layer_swhid_without_any_whiteouts_matches_compute_tar_swhid() {
+        let mut tar_bytes = Vec::new();
+        {
+            let mut builder = tar::Builder::new(&mut tar_bytes);
+            append_tar_file(&mut builder, "a.txt", b"hello");
+            builder.finish().unwrap();
+        }
+
+        let computer = SwhidComputer::new();
+        assert_eq!(
+            computer.compute_layer_swhid(tar_bytes.as_slice()).unwrap(),
+            computer.compute_tar_swhid(tar_bytes.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn redundant_current_dir_component_does_not_change_the_swhid() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+
+        let computer = SwhidComputer::new();
+        let direct = computer.compute_directory_swhid(dir.path()).unwrap();
+        let via_curdir = computer
+            .compute_directory_swhid(dir.path().join("."))
+            .unwrap();
+        assert_eq!(direct, via_curdir);
+    }
+
+    #[test]
+    fn dangling_symlink_does_not_abort_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink("does-not-exist", dir.path().join("broken")).unwrap();
+
+        let computer = SwhidComputer::new();
+        // Neither of these should error out just because the link target is
+        // missing: the link is hashed by its target path string, not by
+        // following it.
+        assert!(computer.compute_directory_swhid(dir.path()).is_ok());
+        assert!(computer.traverse_directory_recursively(dir.path()).is_ok());
+    }
+
+    /// Permission-denied tests are meaningless under root, which bypasses
+    /// the filesystem's discretionary access checks entirely.
+    #[cfg(unix)]
+    fn running_as_root() -> bool {
+        std::process::Command::new("id")
+            .arg("-u")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "0")
+            .unwrap_or(false)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn skip_unreadable_omits_a_permission_denied_subdirectory_and_reports_it() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if running_as_root() {
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), b"keep").unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), b"secret").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let computer = SwhidComputer::builder().skip_unreadable(true).build();
+        let result = computer.compute_directory_swhid_lenient(dir.path());
+
+        // Restore permissions so the tempdir can be cleaned up regardless of
+        // the assertion outcome below.
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let (swhid, skipped) = result.unwrap();
+        assert_eq!(skipped, vec![locked.clone()]);
+
+        let without_locked = tempfile::tempdir().unwrap();
+        fs::write(without_locked.path().join("keep.txt"), b"keep").unwrap();
+        let expected = computer
+            .compute_directory_swhid(without_locked.path())
+            .unwrap();
+        assert_eq!(swhid, expected);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn compute_directory_swhid_lenient_without_skip_unreadable_still_fails() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if running_as_root() {
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let computer = SwhidComputer::new();
+        let result = computer.compute_directory_swhid_lenient(dir.path());
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn builder_with_every_option_matches_equivalent_chained_with_calls() {
+        fn noop_progress(_: &Path) {}
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("run.sh"), b"#!/bin/sh\n").unwrap();
+
+        let via_builder = SwhidComputer::builder()
+            .max_file_size(1024)
+            .swhignore(true)
+            .force_executable(["*.sh"])
+            .skip_unreadable(true)
+            .include_git_dir(true)
+            .progress(noop_progress)
+            .build()
+            .compute_directory_swhid(dir.path())
+            .unwrap();
+
+        #[allow(deprecated)]
+        let via_with_calls = SwhidComputer::new()
+            .with_max_file_size(1024)
+            .with_swhignore(true)
+            .with_force_executable(["*.sh"])
+            .with_skip_unreadable(true)
+            .with_include_git_dir(true)
+            .with_progress(noop_progress)
+            .compute_directory_swhid(dir.path())
+            .unwrap();
+
+        assert_eq!(via_builder, via_with_calls);
+    }
+
+    #[test]
+    fn max_file_size_guard_is_unlimited_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("big.txt");
+        fs::write(&file, vec![0u8; 1024]).unwrap();
+
+        let computer = SwhidComputer::new();
+        assert!(computer.compute_file_swhid(&file).is_ok());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn compute_file_swhid_on_a_symlink_hashes_the_link_target_not_the_linked_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("real.txt"), b"this is the real content").unwrap();
+        let link = dir.path().join("link.txt");
+        std::os::unix::fs::symlink("real.txt", &link).unwrap();
+
+        let computer = SwhidComputer::new();
+        let symlink_swhid = computer.compute_file_swhid(&link).unwrap();
+
+        assert_eq!(
+            symlink_swhid,
+            crate::content::Content::from_data(b"real.txt".to_vec()).swhid()
+        );
+        assert_ne!(
+            symlink_swhid,
+            computer.compute_file_swhid(dir.path().join("real.txt")).unwrap()
+        );
+
+        // Matches how the same symlink hashes as a directory entry.
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        std::os::unix::fs::symlink("../real.txt", dir.path().join("sub/link.txt")).unwrap();
+        let (objects, _) = computer
+            .traverse_directory_with_summary(dir.path().join("sub"))
+            .unwrap();
+        let (_, symlink_entry) = objects.into_iter().next().unwrap();
+        assert_eq!(*symlink_entry.swhid(), computer.compute_file_swhid(dir.path().join("sub/link.txt")).unwrap());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn compute_file_swhid_on_a_dangling_symlink_does_not_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("dangling.txt");
+        std::os::unix::fs::symlink("does-not-exist.txt", &link).unwrap();
+
+        let computer = SwhidComputer::new();
+        assert!(computer.compute_file_swhid(&link).is_ok());
+    }
+
+    #[test]
+    fn recompute_after_change_matches_a_full_recompute_after_editing_a_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/a.txt"), b"before").unwrap();
+        fs::write(dir.path().join("top.txt"), b"unrelated").unwrap();
+
+        let computer = SwhidComputer::new();
+        let mut cache = std::collections::HashMap::new();
+        let initial = computer
+            .recompute_after_change(dir.path(), &[], &mut cache)
+            .unwrap();
+        assert_eq!(initial, computer.compute_directory_swhid(dir.path()).unwrap());
+
+        fs::write(dir.path().join("sub/a.txt"), b"after").unwrap();
+        let updated = computer
+            .recompute_after_change(
+                dir.path(),
+                &[dir.path().join("sub/a.txt")],
+                &mut cache,
+            )
+            .unwrap();
+
+        assert_ne!(updated, initial);
+        assert_eq!(updated, computer.compute_directory_swhid(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn recompute_after_change_caches_an_off_chain_sibling_subdirectory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("changed-sub")).unwrap();
+        fs::write(dir.path().join("changed-sub/a.txt"), b"before").unwrap();
+        fs::create_dir(dir.path().join("sibling-sub")).unwrap();
+        fs::write(dir.path().join("sibling-sub/b.txt"), b"untouched").unwrap();
+
+        let computer = SwhidComputer::new();
+        let mut cache = std::collections::HashMap::new();
+        computer
+            .recompute_after_change(
+                dir.path(),
+                &[dir.path().join("changed-sub/a.txt")],
+                &mut cache,
+            )
+            .unwrap();
+
+        // `sibling-sub` is off the changed file's ancestor chain, so the
+        // first call has to walk it the slow way — but it must still land
+        // in `cache`, or every future call re-walks it from scratch again.
+        assert!(
+            cache.contains_key(&dir.path().join("sibling-sub")),
+            "an off-chain sibling subdirectory must be cached after being walked"
+        );
+
+        // Prove the cached entry is actually used: edit the sibling's file
+        // behind the cache's back and recompute again after a further
+        // change on the chain — if `sibling-sub` were re-walked instead of
+        // read from `cache`, the result would equal a fresh full hash.
+        fs::write(dir.path().join("sibling-sub/b.txt"), b"edited behind the cache").unwrap();
+        fs::write(dir.path().join("changed-sub/a.txt"), b"after").unwrap();
+        let result = computer.recompute_after_change(
+            dir.path(),
+            &[dir.path().join("changed-sub/a.txt")],
+            &mut cache,
+        );
+        assert_ne!(result.unwrap(), computer.compute_directory_swhid(dir.path()).unwrap());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn recompute_after_change_does_not_touch_an_unrelated_unreadable_sibling() {
+        use std::os::unix::fs::PermissionsExt;
+
+        if running_as_root() {
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("locked")).unwrap();
+        fs::write(dir.path().join("locked/secret.txt"), b"shh").unwrap();
+        fs::write(dir.path().join("changed.txt"), b"before").unwrap();
+
+        let computer = SwhidComputer::new();
+        let mut cache = std::collections::HashMap::new();
+        computer
+            .recompute_after_change(dir.path(), &[], &mut cache)
+            .unwrap();
+
+        fs::set_permissions(
+            dir.path().join("locked"),
+            fs::Permissions::from_mode(0o000),
+        )
+        .unwrap();
+
+        fs::write(dir.path().join("changed.txt"), b"after").unwrap();
+        let result = computer.recompute_after_change(
+            dir.path(),
+            &[dir.path().join("changed.txt")],
+            &mut cache,
+        );
+
+        fs::set_permissions(
+            dir.path().join("locked"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        assert!(result.is_ok());
+    }
+}
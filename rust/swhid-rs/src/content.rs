@@ -0,0 +1,741 @@
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, string::ToString, vec::Vec};
+#[cfg(not(feature = "std"))]
+use core::str;
+#[cfg(feature = "std")]
+use std::str;
+
+use crate::error::SwhidError;
+use crate::hash::hash_git_object;
+use crate::swhid::{ObjectType, Swhid};
+
+/// How [`Content::from_file_normalized`] should treat line endings before
+/// hashing. Default is [`LineEnding::Raw`] (no normalization), matching
+/// git's own byte-exact hashing.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// Hash the bytes exactly as read, CRLF or LF or mixed.
+    #[default]
+    Raw,
+    /// Convert every `\r\n` to `\n` before hashing, so a CRLF checkout
+    /// reproduces the SWHID of the canonical LF version.
+    CrlfToLf,
+}
+
+#[cfg(feature = "std")]
+impl LineEnding {
+    fn apply(self, data: Vec<u8>) -> Vec<u8> {
+        match self {
+            LineEnding::Raw => data,
+            LineEnding::CrlfToLf => {
+                let mut out = Vec::with_capacity(data.len());
+                let mut iter = data.iter().copied().peekable();
+                while let Some(byte) = iter.next() {
+                    if byte == b'\r' && iter.peek() == Some(&b'\n') {
+                        continue;
+                    }
+                    out.push(byte);
+                }
+                out
+            }
+        }
+    }
+}
+
+/// In-memory bytes of a file (a "content" object in SWH terms).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Content {
+    data: Vec<u8>,
+    /// The path this content was read from, when built by [`Content::from_file`]
+    /// (or one of its variants). Purely informational — it plays no part in
+    /// [`Content::swhid`], which depends only on `data`.
+    #[cfg(feature = "std")]
+    source_path: Option<std::path::PathBuf>,
+}
+
+impl Content {
+    pub fn from_data(data: impl Into<Vec<u8>>) -> Self {
+        Content {
+            data: data.into(),
+            #[cfg(feature = "std")]
+            source_path: None,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SwhidError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|e| SwhidError::io(path, e))?;
+        Ok(Content {
+            data,
+            source_path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// The path this content was read from, if it was built via
+    /// [`Content::from_file`] or a variant that records one.
+    #[cfg(feature = "std")]
+    pub fn path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
+    }
+
+    /// Read `path` as a symlink and build `Content` from the bytes of its
+    /// *target path string*, not the bytes of whatever it points at —
+    /// Software Heritage identifies a symlink by the text of the link
+    /// itself, the same as git does. Unlike [`Content::from_file`], this
+    /// never follows the link (so it works even for a dangling one) since
+    /// it reads the link with [`std::fs::read_link`] rather than opening
+    /// the path.
+    #[cfg(feature = "std")]
+    pub fn from_symlink(path: impl AsRef<Path>) -> Result<Self, SwhidError> {
+        let path = path.as_ref();
+        let target = fs::read_link(path).map_err(|e| SwhidError::io(path, e))?;
+        Ok(Content {
+            data: target.into_os_string().into_encoded_bytes(),
+            source_path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Like [`Content::from_file`], but skips the extra `stat` call when the
+    /// caller already knows the file's size (e.g. from a `read_dir` entry),
+    /// streaming exactly `len` bytes instead. Errors with
+    /// [`SwhidError::LengthMismatch`] if the file turns out to be shorter or
+    /// longer than `len`.
+    #[cfg(feature = "std")]
+    pub fn from_file_with_len(path: impl AsRef<Path>, len: u64) -> Result<Self, SwhidError> {
+        use std::io::Read;
+
+        let path = path.as_ref();
+        let mut file = fs::File::open(path).map_err(|e| SwhidError::io(path, e))?;
+
+        let mut data = Vec::new();
+        (&mut file)
+            .take(len)
+            .read_to_end(&mut data)
+            .map_err(|e| SwhidError::io(path, e))?;
+        if data.len() as u64 != len {
+            return Err(SwhidError::LengthMismatch {
+                path: path.to_path_buf(),
+                expected: len,
+                actual: data.len() as u64,
+            });
+        }
+
+        // Anything still readable past `len` bytes means the file grew
+        // since the caller measured it.
+        let mut probe = [0u8; 1];
+        let trailing = file.read(&mut probe).map_err(|e| SwhidError::io(path, e))?;
+        if trailing != 0 {
+            return Err(SwhidError::LengthMismatch {
+                path: path.to_path_buf(),
+                expected: len,
+                actual: len + 1,
+            });
+        }
+
+        Ok(Content {
+            data,
+            source_path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Like [`Content::from_file`], but applies `normalize` to the bytes
+    /// before hashing. This deliberately diverges from git's own
+    /// byte-exact hashing when `normalize` isn't [`LineEnding::Raw`] — the
+    /// resulting SWHID matches the canonical LF version of the file, not
+    /// the bytes actually on disk.
+    #[cfg(feature = "std")]
+    pub fn from_file_normalized(
+        path: impl AsRef<Path>,
+        normalize: LineEnding,
+    ) -> Result<Self, SwhidError> {
+        let content = Content::from_file(path)?;
+        Ok(Content {
+            data: normalize.apply(content.data),
+            source_path: content.source_path,
+        })
+    }
+
+    /// Like [`Content::from_file`], but obtains the bytes by memory-mapping
+    /// the file instead of `read`ing it. The mapped bytes are still copied
+    /// into the owned `Vec<u8>` a [`Content`] holds, so this does not avoid
+    /// a heap buffer the size of the file. Produces the identical SWHID.
+    /// Empty files can't be mapped, so those fall back to empty content.
+    #[cfg(all(feature = "mmap", feature = "std"))]
+    pub fn from_file_mmap(path: impl AsRef<Path>) -> Result<Self, SwhidError> {
+        let path = path.as_ref();
+        let file = fs::File::open(path).map_err(|e| SwhidError::io(path, e))?;
+        let len = file
+            .metadata()
+            .map_err(|e| SwhidError::io(path, e))?
+            .len();
+        if len == 0 {
+            return Ok(Content {
+                data: Vec::new(),
+                source_path: Some(path.to_path_buf()),
+            });
+        }
+        // SAFETY: the mapping is read-only and dropped before returning, and
+        // its bytes are only read, to copy them into an owned Vec.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| SwhidError::io(path, e))?;
+        Ok(Content {
+            data: mmap.to_vec(),
+            source_path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Like [`Content::from_file_with_len`], but reads from an async
+    /// `AsyncRead` instead of a file, streaming bytes into memory as they
+    /// arrive instead of requiring the whole blob to be buffered up front
+    /// by the caller. Produces the identical SWHID as the sync path for the
+    /// same bytes. Errors with [`SwhidError::LengthMismatch`] if fewer than
+    /// `len` bytes are available.
+    #[cfg(all(feature = "async", feature = "std"))]
+    pub async fn from_async_reader<R>(mut reader: R, len: u64) -> Result<Self, SwhidError>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        use tokio::io::AsyncReadExt;
+
+        let source = std::path::PathBuf::from("<async reader>");
+        let mut data = Vec::new();
+        (&mut reader)
+            .take(len)
+            .read_to_end(&mut data)
+            .await
+            .map_err(|e| SwhidError::io(&source, e))?;
+        if data.len() as u64 != len {
+            return Err(SwhidError::LengthMismatch {
+                path: source,
+                expected: len,
+                actual: data.len() as u64,
+            });
+        }
+
+        Ok(Content {
+            data,
+            source_path: None,
+        })
+    }
+
+    /// Parse a full `"blob <len>\0<data>"` git object, verifying that the
+    /// declared length matches the number of bytes that follow the header.
+    pub fn from_git_blob(bytes: &[u8]) -> Result<Self, SwhidError> {
+        let header_end = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing NUL header terminator".into()))?;
+        let header = str::from_utf8(&bytes[..header_end])
+            .map_err(|_| SwhidError::InvalidGitObject("header is not valid UTF-8".into()))?;
+        let (git_type, len_str) = header
+            .split_once(' ')
+            .ok_or_else(|| SwhidError::InvalidGitObject(format!("malformed header: {header}")))?;
+        if git_type != "blob" {
+            return Err(SwhidError::InvalidGitObject(format!(
+                "expected blob object, got {git_type}"
+            )));
+        }
+        let declared_len: usize = len_str
+            .parse()
+            .map_err(|_| SwhidError::InvalidGitObject(format!("invalid length: {len_str}")))?;
+        let data = &bytes[header_end + 1..];
+        if data.len() != declared_len {
+            return Err(SwhidError::InvalidGitObject(format!(
+                "declared length {declared_len} does not match actual length {}",
+                data.len()
+            )));
+        }
+        Ok(Content {
+            data: data.to_vec(),
+            #[cfg(feature = "std")]
+            source_path: None,
+        })
+    }
+
+    /// Hash `data[offset..offset + len]` as a blob without copying the rest
+    /// of `data`, useful for byte ranges inside a memory-mapped file.
+    pub fn from_range(data: &[u8], offset: usize, len: usize) -> Result<Self, SwhidError> {
+        let end = offset
+            .checked_add(len)
+            .ok_or_else(|| SwhidError::InvalidGitObject("range overflows usize".into()))?;
+        let slice = data.get(offset..end).ok_or_else(|| {
+            SwhidError::InvalidGitObject(format!(
+                "range {offset}..{end} out of bounds for {} bytes",
+                data.len()
+            ))
+        })?;
+        Ok(Content {
+            data: slice.to_vec(),
+            #[cfg(feature = "std")]
+            source_path: None,
+        })
+    }
+
+    /// Hash `data[start..end]` as a standalone content object, e.g. to
+    /// produce the core SWHID a `lines=`-qualified identifier refers to.
+    /// Unlike [`Content::from_range`] (which takes an offset and a length),
+    /// this takes the range endpoints directly. Rejects `start > end` or an
+    /// `end` past `data.len()`.
+    pub fn swhid_of_range(data: &[u8], start: usize, end: usize) -> Result<Swhid, SwhidError> {
+        if start > end {
+            return Err(SwhidError::InvalidGitObject(format!(
+                "range start {start} is after end {end}"
+            )));
+        }
+        Ok(Content::from_range(data, start, end - start)?.swhid())
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Whether this content looks binary, using git's own heuristic: a NUL
+    /// byte anywhere in the first 8000 bytes. Cheap and widely used, but
+    /// still just a heuristic — e.g. UTF-16 text trips it.
+    pub fn is_binary(&self) -> bool {
+        let prefix_len = self.data.len().min(8000);
+        self.data[..prefix_len].contains(&0)
+    }
+
+    pub fn swhid(&self) -> Swhid {
+        let hash = hash_git_object("blob", &self.data);
+        Swhid::new(ObjectType::Content, hash)
+    }
+
+    /// The id [`Content::swhid`] would produce if git's canonical `"blob"`
+    /// header were replaced with `git_type`, for research into how the
+    /// header choice affects the resulting hash.
+    ///
+    /// **Not a SWHID.** Software Heritage content identifiers are always
+    /// hashed with the `"blob"` header — this method exists purely to
+    /// reproduce, for experimentation, the id an alternate header would
+    /// have produced. Never persist, publish, or compare the result
+    /// against a real [`Content::swhid`]; the returned [`Swhid`] is
+    /// wrapped as [`ObjectType::Content`] only because that's the
+    /// convenient `swh:1:<type>:<hex>` formatter, not because the id means
+    /// anything the spec recognizes.
+    pub fn swhid_with_type(&self, git_type: &str) -> Swhid {
+        let hash = hash_git_object(git_type, &self.data);
+        Swhid::new(ObjectType::Content, hash)
+    }
+
+    /// A SWHID computed over this content's bytes after normalizing line
+    /// endings with `line_ending`, e.g. to match a CRLF checkout against the
+    /// LF version of the same text for cross-platform dedup.
+    ///
+    /// This is a **derived identifier**, not a spec SWHID: two contents
+    /// with different bytes (and thus different [`Content::swhid`] values)
+    /// can share a `normalized_swhid`. Never persist or publish this in
+    /// place of `swhid()` — it's only meaningful as a secondary index
+    /// within a pipeline that knows it isn't the real content identifier.
+    #[cfg(feature = "std")]
+    pub fn normalized_swhid(&self, line_ending: LineEnding) -> Swhid {
+        let normalized = line_ending.apply(self.data.clone());
+        let hash = hash_git_object("blob", &normalized);
+        Swhid::new(ObjectType::Content, hash)
+    }
+
+    /// An extra BLAKE2s-256 digest of this content's bytes, for pipelines
+    /// that want to precompute and store it alongside the SWHID. Plays no
+    /// part in [`Content::swhid`].
+    #[cfg(feature = "blake2")]
+    pub fn blake2s256(&self) -> [u8; 32] {
+        crate::hash::blake2s_256(&self.data)
+    }
+
+    /// Guess this content's MIME type from a handful of well-known magic
+    /// byte sequences (PNG, JPEG, GIF, PDF, ZIP, gzip, ELF, shebang
+    /// scripts), falling back to `text/plain` for data that decodes as
+    /// valid, printable-or-whitespace UTF-8. Returns `None` when nothing
+    /// matches and the bytes don't look like text — this is a cheap
+    /// heuristic, not a real type sniffer, and is meant for eyeballing
+    /// traversal output rather than driving any decision that matters.
+    #[cfg(feature = "mime")]
+    pub fn guess_mime(&self) -> Option<&'static str> {
+        const SIGNATURES: &[(&[u8], &str)] = &[
+            (b"\x89PNG\r\n\x1a\n", "image/png"),
+            (b"\xff\xd8\xff", "image/jpeg"),
+            (b"GIF87a", "image/gif"),
+            (b"GIF89a", "image/gif"),
+            (b"%PDF-", "application/pdf"),
+            (b"PK\x03\x04", "application/zip"),
+            (b"\x1f\x8b", "application/gzip"),
+            (b"\x7fELF", "application/x-elf"),
+        ];
+        for (magic, mime) in SIGNATURES {
+            if self.data.starts_with(magic) {
+                return Some(mime);
+            }
+        }
+        if self.data.starts_with(b"#!") {
+            return Some("text/x-shellscript");
+        }
+        if !self.data.is_empty() && str::from_utf8(&self.data).is_ok_and(is_printable_text) {
+            return Some("text/plain");
+        }
+        None
+    }
+}
+
+/// Whether `text` looks like human-readable text rather than binary data
+/// that merely happens to be valid UTF-8: every character is either
+/// whitespace or non-control.
+#[cfg(feature = "mime")]
+fn is_printable_text(text: &str) -> bool {
+    text.chars().all(|c| !c.is_control() || c.is_whitespace())
+}
+
+/// Compute the `swh:1:cnt:...` string for raw bytes with no filesystem or
+/// [`Content`] struct involved, suitable for exposing across an FFI/wasm
+/// boundary (e.g. `#[wasm_bindgen]`). Matches `Content::from_data(data).swhid().to_string()`
+/// and the CLI's stdin path exactly.
+pub fn compute_content_swhid_str(data: &[u8]) -> String {
+    Content::from_data(data.to_vec()).swhid().to_string()
+}
+
+/// Compute a content SWHID by streaming `len` bytes from `reader` through
+/// the hasher instead of first reading them into a `Vec<u8>` (what every
+/// other `Content` constructor does). `path` is only used to label I/O
+/// errors, the way [`Content::from_file_with_len`]'s is.
+///
+/// Unlike `Vec<u8>::len()`, `len` is a `u64`, not bounded by `usize` — on a
+/// 32-bit target this lets a file too large to ever hold as a `Content`
+/// there still be hashed, as long as the caller only needs the resulting
+/// [`Swhid`] back, not the bytes.
+#[cfg(feature = "std")]
+pub fn swhid_of_reader(
+    path: impl AsRef<Path>,
+    mut reader: impl std::io::Read,
+    len: u64,
+) -> Result<Swhid, SwhidError> {
+    let path = path.as_ref();
+    let hash = crate::hash::hash_git_object_streamed("blob", len, &mut reader)
+        .map_err(|e| SwhidError::io(path, e))?;
+    Ok(Swhid::new(ObjectType::Content, hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn blake2s256_is_independent_of_swhid() {
+        let content = Content::from_data(b"hello".to_vec());
+        assert_eq!(content.blake2s256(), crate::hash::blake2s_256(b"hello"));
+        assert_ne!(content.swhid().hash().len(), content.blake2s256().len());
+    }
+
+    #[test]
+    fn empty_content_matches_git_empty_blob() {
+        let content = Content::from_data(Vec::new());
+        assert_eq!(
+            content.swhid().to_string(),
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        );
+    }
+
+    #[test]
+    fn from_git_blob_extracts_content() {
+        let blob = b"blob 5\0hello";
+        let content = Content::from_git_blob(blob).unwrap();
+        assert_eq!(content.data(), b"hello");
+    }
+
+    #[test]
+    fn from_git_blob_rejects_length_mismatch() {
+        let blob = b"blob 99\0hello";
+        assert!(Content::from_git_blob(blob).is_err());
+    }
+
+    #[test]
+    fn from_range_matches_hashing_the_slice_directly() {
+        let archive = b"xxxhelloxxx";
+        let ranged = Content::from_range(archive, 3, 5).unwrap();
+        let direct = Content::from_data(archive[3..8].to_vec());
+        assert_eq!(ranged.swhid(), direct.swhid());
+    }
+
+    #[test]
+    fn from_range_rejects_out_of_bounds() {
+        let archive = b"short";
+        assert!(Content::from_range(archive, 3, 50).is_err());
+    }
+
+    #[test]
+    fn swhid_of_range_matches_hashing_the_slice_directly() {
+        let file = b"line1\nline2\nline3\n";
+        let swhid = Content::swhid_of_range(file, 6, 11).unwrap();
+        assert_eq!(swhid, Content::from_data(b"line2".to_vec()).swhid());
+    }
+
+    #[test]
+    fn swhid_of_range_rejects_start_after_end() {
+        let file = b"hello";
+        assert!(Content::swhid_of_range(file, 3, 1).is_err());
+    }
+
+    #[test]
+    fn swhid_of_range_rejects_end_past_data_len() {
+        let file = b"hello";
+        assert!(Content::swhid_of_range(file, 0, 50).is_err());
+    }
+
+    #[test]
+    fn swhid_of_range_accepts_an_empty_range() {
+        let file = b"hello";
+        assert_eq!(
+            Content::swhid_of_range(file, 2, 2).unwrap(),
+            Content::from_data(Vec::new()).swhid()
+        );
+    }
+
+    #[test]
+    fn swhid_of_reader_matches_hashing_the_same_bytes_in_memory() {
+        let data = b"hello, streaming world".repeat(100);
+        let swhid =
+            swhid_of_reader("stream.bin", data.as_slice(), data.len() as u64).unwrap();
+        assert_eq!(swhid, Content::from_data(data).swhid());
+    }
+
+    #[test]
+    fn is_binary_detects_a_nul_byte_anywhere_in_the_first_8000_bytes() {
+        assert!(!Content::from_data(b"hello world".to_vec()).is_binary());
+        assert!(Content::from_data(b"hello\0world".to_vec()).is_binary());
+
+        let mut far_nul = vec![b'a'; 9000];
+        far_nul[8500] = 0;
+        assert!(!Content::from_data(far_nul).is_binary());
+
+        let mut near_nul = vec![b'a'; 9000];
+        near_nul[7999] = 0;
+        assert!(Content::from_data(near_nul).is_binary());
+    }
+
+    #[test]
+    fn normalized_swhid_ignores_crlf_differences_but_swhid_does_not() {
+        let lf = Content::from_data(b"hello\nworld\n".to_vec());
+        let crlf = Content::from_data(b"hello\r\nworld\r\n".to_vec());
+
+        assert_ne!(lf.swhid(), crlf.swhid());
+        assert_eq!(
+            lf.normalized_swhid(LineEnding::CrlfToLf),
+            crlf.normalized_swhid(LineEnding::CrlfToLf)
+        );
+    }
+
+    #[test]
+    fn normalized_swhid_with_raw_matches_the_canonical_swhid() {
+        let content = Content::from_data(b"hello\r\nworld\r\n".to_vec());
+        assert_eq!(content.normalized_swhid(LineEnding::Raw), content.swhid());
+    }
+
+    #[test]
+    fn swhid_with_type_matches_swhid_when_the_header_is_blob() {
+        let content = Content::from_data(b"hello".to_vec());
+        assert_eq!(content.swhid_with_type("blob"), content.swhid());
+    }
+
+    #[test]
+    fn swhid_with_type_changes_the_id_when_the_header_changes() {
+        let content = Content::from_data(b"hello".to_vec());
+        assert_ne!(content.swhid_with_type("tree"), content.swhid());
+        assert_eq!(
+            content.swhid_with_type("tree").hash(),
+            &hash_git_object("tree", b"hello")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn from_symlink_hashes_the_target_path_string_not_the_target_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("real.txt"), b"this is the real content").unwrap();
+        let link = dir.path().join("link.txt");
+        std::os::unix::fs::symlink("real.txt", &link).unwrap();
+
+        let from_symlink = Content::from_symlink(&link).unwrap();
+        assert_eq!(from_symlink.swhid(), Content::from_data(b"real.txt".to_vec()).swhid());
+        assert_ne!(from_symlink.swhid(), Content::from_file(&link).unwrap().swhid());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn from_symlink_works_even_when_the_target_does_not_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("dangling.txt");
+        std::os::unix::fs::symlink("does-not-exist.txt", &link).unwrap();
+
+        let content = Content::from_symlink(&link).unwrap();
+        assert_eq!(content.swhid(), Content::from_data(b"does-not-exist.txt".to_vec()).swhid());
+    }
+
+    #[test]
+    fn from_file_records_source_path_but_from_data_does_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, b"hello").unwrap();
+
+        let from_file = Content::from_file(&path).unwrap();
+        assert_eq!(from_file.path(), Some(path.as_path()));
+
+        let from_data = Content::from_data(b"hello".to_vec());
+        assert_eq!(from_data.path(), None);
+
+        // The source path is purely informational: it doesn't affect the id.
+        assert_eq!(from_file.swhid(), from_data.swhid());
+    }
+
+    #[test]
+    fn from_file_with_len_matches_from_file_when_len_is_correct() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, b"hello, streaming").unwrap();
+        assert_eq!(
+            Content::from_file_with_len(&path, 16).unwrap().swhid(),
+            Content::from_file(&path).unwrap().swhid()
+        );
+    }
+
+    #[test]
+    fn from_file_with_len_rejects_a_shorter_actual_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, b"short").unwrap();
+        assert!(Content::from_file_with_len(&path, 50).is_err());
+    }
+
+    #[test]
+    fn from_file_with_len_rejects_a_longer_actual_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, b"longer than claimed").unwrap();
+        assert!(Content::from_file_with_len(&path, 5).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn from_async_reader_matches_from_data() {
+        let data = b"hello, async".to_vec();
+        let reader = tokio::io::BufReader::new(data.as_slice());
+        let from_async = Content::from_async_reader(reader, data.len() as u64)
+            .await
+            .unwrap();
+        assert_eq!(from_async.swhid(), Content::from_data(data).swhid());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn from_async_reader_rejects_a_shorter_actual_stream() {
+        let data = b"short".to_vec();
+        let reader = tokio::io::BufReader::new(data.as_slice());
+        assert!(Content::from_async_reader(reader, 50).await.is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn from_file_mmap_matches_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, b"hello, mmap").unwrap();
+        assert_eq!(
+            Content::from_file(&path).unwrap().swhid(),
+            Content::from_file_mmap(&path).unwrap().swhid()
+        );
+    }
+
+    #[test]
+    fn compute_content_swhid_str_matches_content_swhid() {
+        let data = b"hello, wasm";
+        assert_eq!(
+            compute_content_swhid_str(data),
+            Content::from_data(data.to_vec()).swhid().to_string()
+        );
+    }
+
+    #[test]
+    fn from_file_normalized_defaults_to_raw_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crlf.txt");
+        fs::write(&path, b"line1\r\nline2\r\n").unwrap();
+        assert_eq!(
+            Content::from_file_normalized(&path, LineEnding::default())
+                .unwrap()
+                .swhid(),
+            Content::from_file(&path).unwrap().swhid()
+        );
+    }
+
+    #[test]
+    fn from_file_normalized_converts_crlf_to_lf() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crlf.txt");
+        fs::write(&path, b"line1\r\nline2\r\n").unwrap();
+        let normalized = Content::from_file_normalized(&path, LineEnding::CrlfToLf).unwrap();
+        let expected = Content::from_data(b"line1\nline2\n".to_vec());
+        assert_eq!(normalized.swhid(), expected.swhid());
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn guess_mime_recognizes_common_magic_bytes() {
+        assert_eq!(
+            Content::from_data(b"\x89PNG\r\n\x1a\nrest".to_vec()).guess_mime(),
+            Some("image/png")
+        );
+        assert_eq!(
+            Content::from_data(b"\xff\xd8\xffrest".to_vec()).guess_mime(),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            Content::from_data(b"PK\x03\x04rest".to_vec()).guess_mime(),
+            Some("application/zip")
+        );
+        assert_eq!(
+            Content::from_data(b"#!/bin/sh\necho hi\n".to_vec()).guess_mime(),
+            Some("text/x-shellscript")
+        );
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn guess_mime_falls_back_to_text_plain_for_printable_utf8() {
+        assert_eq!(
+            Content::from_data(b"hello, world\n".to_vec()).guess_mime(),
+            Some("text/plain")
+        );
+    }
+
+    #[cfg(feature = "mime")]
+    #[test]
+    fn guess_mime_returns_none_for_unrecognized_binary() {
+        let data = vec![0u8, 1, 2, 3, 0xff, 0xfe, 0x00, 0x01];
+        assert_eq!(Content::from_data(data).guess_mime(), None);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn from_file_mmap_handles_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty.txt");
+        fs::write(&path, b"").unwrap();
+        assert_eq!(
+            Content::from_file_mmap(&path).unwrap().swhid(),
+            Content::from_data(Vec::new()).swhid()
+        );
+    }
+}
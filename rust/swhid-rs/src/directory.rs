@@ -0,0 +1,1306 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::SwhidError;
+use crate::hash::{hash_git_object, start_git_object_hash, GitHasher, GitSha1, Sha1Backend};
+use crate::swhid::{ObjectType, Swhid};
+use crate::verify::SelfConsistent;
+
+/// The git file mode of a directory entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permissions {
+    Regular,
+    Executable,
+    Symlink,
+    Directory,
+    Submodule,
+}
+
+impl Permissions {
+    pub fn git_mode(&self) -> &'static str {
+        match self {
+            Permissions::Regular => "100644",
+            Permissions::Executable => "100755",
+            Permissions::Symlink => "120000",
+            Permissions::Directory => "40000",
+            Permissions::Submodule => "160000",
+        }
+    }
+
+    /// Derive permissions from a raw `st_mode` value, as returned by `stat`.
+    pub fn from_mode(mode: u32) -> Self {
+        match mode & 0o170000 {
+            0o120000 => Permissions::Symlink,
+            0o040000 => Permissions::Directory,
+            0o160000 => Permissions::Submodule,
+            _ => {
+                if mode & 0o111 != 0 {
+                    Permissions::Executable
+                } else {
+                    Permissions::Regular
+                }
+            }
+        }
+    }
+
+    /// Parse the ASCII octal mode string found in a git tree entry
+    /// (e.g. `b"100644"` or the non-canonical `b"040000"`).
+    pub fn from_git_mode_bytes(mode: &[u8]) -> Result<Self, SwhidError> {
+        let mode_str = std::str::from_utf8(mode)
+            .map_err(|_| SwhidError::InvalidGitObject("non-ASCII mode".into()))?;
+        let mode = u32::from_str_radix(mode_str, 8)
+            .map_err(|_| SwhidError::InvalidGitObject(format!("invalid mode: {mode_str}")))?;
+        Ok(Permissions::from_mode(mode))
+    }
+
+    /// Derive permissions from filesystem metadata, the cross-platform way:
+    /// on Unix this reads the real `st_mode` exec bits via [`Self::from_mode`];
+    /// on platforms with no concept of an exec bit (Windows) every regular
+    /// file is non-executable, and only directories/symlinks are
+    /// distinguished via [`std::fs::Metadata::file_type`].
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Permissions::from_mode(metadata.mode())
+        }
+        #[cfg(not(unix))]
+        {
+            let file_type = metadata.file_type();
+            if file_type.is_dir() {
+                Permissions::Directory
+            } else if file_type.is_symlink() {
+                Permissions::Symlink
+            } else {
+                Permissions::Regular
+            }
+        }
+    }
+}
+
+/// A single entry in a directory's manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub name: Vec<u8>,
+    pub permissions: Permissions,
+    /// The git-sha1 of the entry's target (a content, directory or revision
+    /// object, depending on `permissions`).
+    pub target: GitSha1,
+}
+
+impl DirectoryEntry {
+    /// The number of bytes [`DirectoryEntry::write_manifest_entry`] would
+    /// write for this entry, without actually writing them — needed
+    /// upfront by [`Directory::compute_hash`] to size the `"tree <len>\0"`
+    /// header before any entry bytes are hashed.
+    fn manifest_entry_len(&self) -> usize {
+        self.permissions.git_mode().len() + 1 + self.name.len() + 1 + 20
+    }
+
+    /// Write this entry's encoding within a directory's manifest — `<mode>
+    /// SP <name> NUL <20-byte target>` — the same bytes [`Directory`]'s
+    /// regenerated manifest contains for this entry, to `w`. Exposed so a
+    /// directory with tens of thousands of entries can be hashed by
+    /// streaming each entry straight into a hasher (see
+    /// [`Directory::compute_hash`]) instead of first collecting the whole
+    /// manifest into one `Vec<u8>`.
+    pub fn write_manifest_entry(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        w.write_all(self.permissions.git_mode().as_bytes())?;
+        w.write_all(b" ")?;
+        w.write_all(&self.name)?;
+        w.write_all(b"\0")?;
+        w.write_all(self.target.as_bytes())
+    }
+}
+
+/// The sort key git uses to order tree entries: the entry's name, with a
+/// trailing `/` appended when it's a directory. This is the subtlety that
+/// makes `"foo"` (a file named `foo.txt` would sort before it) compare as
+/// if it were `"foo/"`, so e.g. a directory named `foo` sorts *after* a
+/// file named `foo.txt` (`.` is `0x2e`, `/` is `0x2f`).
+pub fn git_tree_sort_key(name: &[u8], is_dir: bool) -> Vec<u8> {
+    let mut key = name.to_vec();
+    if is_dir {
+        key.push(b'/');
+    }
+    key
+}
+
+fn sort_key(entry: &DirectoryEntry) -> Vec<u8> {
+    git_tree_sort_key(&entry.name, matches!(entry.permissions, Permissions::Directory))
+}
+
+pub fn git_tree_entry_cmp(a: &DirectoryEntry, b: &DirectoryEntry) -> Ordering {
+    sort_key(a).cmp(&sort_key(b))
+}
+
+/// Whether `metadata` describes a FIFO, Unix domain socket, or block/char
+/// device: something git has no object representation for, and which
+/// [`Content::from_file`](crate::content::Content::from_file) must never be
+/// pointed at, since opening a FIFO with no writer blocks forever and a
+/// device node's "content" isn't meaningful data to hash. Always `false` on
+/// platforms with no such distinction (Windows).
+#[cfg(unix)]
+pub(crate) fn is_unsupported_file_type(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    let file_type = metadata.file_type();
+    file_type.is_fifo() || file_type.is_socket() || file_type.is_block_device() || file_type.is_char_device()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_unsupported_file_type(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// The git-sha1 of a symlink's content, i.e. the bytes of its target path
+/// string (not the bytes of whatever the link points at). Works even when
+/// the link target doesn't exist, since it never stats or reads through it.
+pub(crate) fn hash_symlink_target(path: &Path) -> Result<[u8; 20], SwhidError> {
+    let target = fs::read_link(path).map_err(|e| SwhidError::io(path, e))?;
+    Ok(hash_git_object("blob", target.into_os_string().into_encoded_bytes().as_slice()))
+}
+
+/// Abstracts the filesystem calls [`Directory::from_disk_filtered`] and
+/// friends need to walk a tree — listing a directory, classifying an entry,
+/// reading a file's bytes, and resolving a symlink's target — behind a
+/// trait, so the same traversal logic can walk something that isn't the
+/// local disk (an SFTP/rsync-mounted remote, an in-memory fixture for
+/// tests) by implementing this trait instead of going through `std::fs`.
+/// [`LocalFileSystem`] is the default implementation and is what
+/// [`Directory::from_disk_with_hash_fn`] and [`Directory::from_disk_filtered`]
+/// use under the hood; [`Directory::from_disk_with_allowlist`] reads local
+/// disk through `std::fs` directly.
+pub trait FileSystem {
+    /// List the immediate children of `path`, in any order.
+    fn read_dir(&self, path: &Path) -> Result<Vec<std::path::PathBuf>, SwhidError>;
+
+    /// Classify `path` without following it if it's a symlink (i.e.
+    /// `lstat`, not `stat`) — git has only one symlink mode (`120000`), so
+    /// an implementation that instead reported the *followed* target's
+    /// mode would wrongly emit `100755` for a symlink pointing at an
+    /// executable file. Returns [`SwhidError::UnsupportedFileType`] for a
+    /// FIFO, socket, or device node.
+    fn metadata(&self, path: &Path) -> Result<Permissions, SwhidError>;
+
+    /// Read the full contents of the regular file at `path`.
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, SwhidError>;
+
+    /// Read the raw target string of the symlink at `path`, without
+    /// following it.
+    fn read_link(&self, path: &Path) -> Result<std::path::PathBuf, SwhidError>;
+}
+
+/// The default [`FileSystem`]: reads straight from local disk via
+/// `std::fs`, exactly as every `Directory::from_disk_*` method did before
+/// traversal was abstracted behind the trait.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalFileSystem;
+
+impl FileSystem for LocalFileSystem {
+    fn read_dir(&self, path: &Path) -> Result<Vec<std::path::PathBuf>, SwhidError> {
+        fs::read_dir(path)
+            .map_err(|e| SwhidError::io(path, e))?
+            .map(|entry| Ok(entry.map_err(|e| SwhidError::io(path, e))?.path()))
+            .collect()
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Permissions, SwhidError> {
+        let metadata = fs::symlink_metadata(path).map_err(|e| SwhidError::io(path, e))?;
+        let permissions = Permissions::from_metadata(&metadata);
+        if !matches!(permissions, Permissions::Directory | Permissions::Symlink)
+            && is_unsupported_file_type(&metadata)
+        {
+            return Err(SwhidError::UnsupportedFileType(path.to_path_buf()));
+        }
+        Ok(permissions)
+    }
+
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, SwhidError> {
+        fs::read(path).map_err(|e| SwhidError::io(path, e))
+    }
+
+    fn read_link(&self, path: &Path) -> Result<std::path::PathBuf, SwhidError> {
+        fs::read_link(path).map_err(|e| SwhidError::io(path, e))
+    }
+}
+
+/// A directory object: an ordered manifest of entries.
+#[derive(Debug, Clone, Default)]
+pub struct Directory {
+    entries: Vec<DirectoryEntry>,
+    /// The exact bytes this directory was parsed from, when it was built by
+    /// [`Directory::from_raw_manifest`] rather than assembled canonically.
+    /// Needed for objects whose on-disk encoding doesn't match what we'd
+    /// regenerate (e.g. a non-canonical mode string), so their original
+    /// SWHID can still be reproduced.
+    raw_manifest: Option<Vec<u8>>,
+}
+
+/// Adapts a [`GitHasher`] to [`std::io::Write`], so
+/// [`DirectoryEntry::write_manifest_entry`] can be fed entries directly
+/// inside [`Directory::compute_hash`]'s streaming path without that path
+/// needing its own byte-juggling.
+struct HasherSink<'a, B: Sha1Backend>(&'a mut GitHasher<B>);
+
+impl<B: Sha1Backend> std::io::Write for HasherSink<'_, B> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Directory {
+    pub fn new() -> Self {
+        Directory::default()
+    }
+
+    pub fn add_entry(&mut self, entry: DirectoryEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn entries(&self) -> &[DirectoryEntry] {
+        &self.entries
+    }
+
+    fn sorted_entries(&self) -> Vec<&DirectoryEntry> {
+        let mut entries: Vec<&DirectoryEntry> = self.entries.iter().collect();
+        entries.sort_by(|a, b| git_tree_entry_cmp(a, b));
+        entries
+    }
+
+    fn manifest(&self) -> Vec<u8> {
+        let mut manifest = Vec::new();
+        for entry in self.sorted_entries() {
+            entry.write_manifest_entry(&mut manifest).expect("writing to a Vec<u8> cannot fail");
+        }
+        manifest
+    }
+
+    /// Hash `raw_manifest` directly when present (for directories whose
+    /// original encoding doesn't round-trip, e.g. a non-canonical entry
+    /// order), otherwise stream the canonical manifest straight into a
+    /// hasher one entry at a time via [`DirectoryEntry::write_manifest_entry`],
+    /// rather than collecting it into a `Vec<u8>` first — this keeps peak
+    /// memory flat regardless of how many entries the directory has.
+    pub fn compute_hash(&self) -> [u8; 20] {
+        match &self.raw_manifest {
+            Some(raw) => hash_git_object("tree", raw),
+            None => {
+                let entries = self.sorted_entries();
+                let len: usize = entries.iter().map(|entry| entry.manifest_entry_len()).sum();
+                let mut hasher = start_git_object_hash("tree", len as u64);
+                let mut sink = HasherSink(&mut hasher);
+                for entry in entries {
+                    entry.write_manifest_entry(&mut sink).expect("writing to a GitHasher cannot fail");
+                }
+                hasher.finalize()
+            }
+        }
+    }
+
+    pub fn swhid(&self) -> Swhid {
+        Swhid::new(ObjectType::Directory, self.compute_hash())
+    }
+
+    /// Identical to [`Directory::swhid`]: this crate never caches the
+    /// computed hash, so `swhid` already takes `&self` and is a one-shot
+    /// immutable computation. Provided as an explicit alias for callers
+    /// migrating from an API where hashing required `&mut self`.
+    pub fn compute_swhid(&self) -> Swhid {
+        self.swhid()
+    }
+
+    /// Set the exact bytes this directory should hash as, overriding the
+    /// canonically regenerated manifest. Mirrors `Revision`/`Release`/
+    /// `Snapshot`'s `raw_manifest` field, for a directory assembled from
+    /// entries in code (rather than parsed via
+    /// [`Directory::from_raw_manifest`]) that still needs to reproduce a
+    /// quirky, non-canonically-encoded SWHID.
+    pub fn with_raw_manifest(mut self, raw_manifest: impl Into<Vec<u8>>) -> Self {
+        self.raw_manifest = Some(raw_manifest.into());
+        self
+    }
+
+    /// Build a `Directory` by walking a single level of `path` on disk,
+    /// hashing each child with `hash_fn`. Subdirectories are recursed into
+    /// eagerly and symlinks are hashed from their target path string, so
+    /// `hash_fn` only ever sees regular files.
+    pub fn from_disk_with_hash_fn<F>(path: &Path, hash_fn: &mut F) -> Result<Directory, SwhidError>
+    where
+        F: FnMut(&Path) -> Result<[u8; 20], SwhidError>,
+    {
+        Directory::from_disk_filtered(path, hash_fn, &|_| false, &|_, permissions| permissions)
+    }
+
+    /// Like [`Directory::from_disk_with_hash_fn`], but skips any entry for
+    /// which `should_skip` returns `true` before it's hashed or recursed
+    /// into, and replaces a non-directory entry's detected [`Permissions`]
+    /// with whatever `permission_override` returns (symlinks are passed
+    /// through unchanged, since overriding those wouldn't make sense).
+    pub fn from_disk_filtered<F, S, P>(
+        path: &Path,
+        hash_fn: &mut F,
+        should_skip: &S,
+        permission_override: &P,
+    ) -> Result<Directory, SwhidError>
+    where
+        F: FnMut(&Path) -> Result<[u8; 20], SwhidError>,
+        S: Fn(&Path) -> bool,
+        P: Fn(&Path, Permissions) -> Permissions,
+    {
+        Directory::from_fs_filtered(&LocalFileSystem, path, hash_fn, should_skip, permission_override)
+    }
+
+    /// Like [`Directory::from_disk_filtered`], but walks `fs` instead of
+    /// always going straight to local disk — the only difference is that
+    /// directory listing, entry classification, and symlink resolution go
+    /// through the [`FileSystem`] trait. `hash_fn` is still given just the
+    /// entry's path; a caller backing this with a remote [`FileSystem`]
+    /// should have `hash_fn` read through that same backend (e.g. via
+    /// `fs.read_file(path)`) rather than assuming local disk.
+    pub fn from_fs_filtered<FS, F, S, P>(
+        fs: &FS,
+        path: &Path,
+        hash_fn: &mut F,
+        should_skip: &S,
+        permission_override: &P,
+    ) -> Result<Directory, SwhidError>
+    where
+        FS: FileSystem,
+        F: FnMut(&Path) -> Result<[u8; 20], SwhidError>,
+        S: Fn(&Path) -> bool,
+        P: Fn(&Path, Permissions) -> Permissions,
+    {
+        let mut directory = Directory::new();
+        for entry_path in fs.read_dir(path)? {
+            if should_skip(&entry_path) {
+                continue;
+            }
+            let permissions = fs.metadata(&entry_path)?;
+            let name = entry_path
+                .file_name()
+                .ok_or_else(|| SwhidError::InvalidGitObject(format!("no file name: {}", entry_path.display())))?
+                .to_os_string()
+                .into_encoded_bytes();
+
+            let (permissions, target) = if permissions == Permissions::Directory {
+                let subdir = Directory::from_fs_filtered(
+                    fs,
+                    &entry_path,
+                    hash_fn,
+                    should_skip,
+                    permission_override,
+                )?;
+                (Permissions::Directory, subdir.compute_hash())
+            } else if permissions == Permissions::Symlink {
+                // The content of a symlink entry is the *bytes of its
+                // target path string*, not whatever `hash_fn` would read
+                // by following the link.
+                let link_target = fs.read_link(&entry_path)?;
+                let hash = hash_git_object(
+                    "blob",
+                    link_target.into_os_string().into_encoded_bytes().as_slice(),
+                );
+                (Permissions::Symlink, hash)
+            } else {
+                let target = hash_fn(&entry_path)?;
+                (permission_override(&entry_path, permissions), target)
+            };
+
+            directory.add_entry(DirectoryEntry {
+                name,
+                permissions,
+                target: target.into(),
+            });
+        }
+        Ok(directory)
+    }
+
+    /// Like [`Directory::from_disk_with_hash_fn`], but only includes entries
+    /// whose path relative to `path` (components joined with `/`) appears in
+    /// `keep`. This is an allowlist, unlike [`Directory::from_disk_filtered`]'s
+    /// skip predicate: it's meant for reproducing the SWHID of a specific
+    /// committed tree from a working directory that also has untracked
+    /// files lying around (e.g. `keep` built from `git ls-files`), where
+    /// listing what to exclude would be both unbounded and fragile. A
+    /// subdirectory left with no allowed entries is dropped entirely, since
+    /// git trees can't contain empty directories.
+    pub fn from_disk_with_allowlist<F>(
+        path: &Path,
+        keep: &std::collections::HashSet<Vec<u8>>,
+        hash_fn: &mut F,
+    ) -> Result<Directory, SwhidError>
+    where
+        F: FnMut(&Path) -> Result<[u8; 20], SwhidError>,
+    {
+        Self::from_disk_with_allowlist_at(path, &[], keep, hash_fn)
+    }
+
+    fn from_disk_with_allowlist_at<F>(
+        path: &Path,
+        prefix: &[u8],
+        keep: &std::collections::HashSet<Vec<u8>>,
+        hash_fn: &mut F,
+    ) -> Result<Directory, SwhidError>
+    where
+        F: FnMut(&Path) -> Result<[u8; 20], SwhidError>,
+    {
+        let mut directory = Directory::new();
+        let read_dir = fs::read_dir(path).map_err(|e| SwhidError::io(path, e))?;
+        for entry in read_dir {
+            let entry = entry.map_err(|e| SwhidError::io(path, e))?;
+            let entry_path = entry.path();
+            let name = entry.file_name().into_encoded_bytes();
+            let mut relative = prefix.to_vec();
+            if !relative.is_empty() {
+                relative.push(b'/');
+            }
+            relative.extend_from_slice(&name);
+
+            let metadata = entry.metadata().map_err(|e| SwhidError::io(&entry_path, e))?;
+            if metadata.is_dir() {
+                let subdir =
+                    Self::from_disk_with_allowlist_at(&entry_path, &relative, keep, hash_fn)?;
+                if subdir.entries.is_empty() {
+                    continue;
+                }
+                directory.add_entry(DirectoryEntry {
+                    name,
+                    permissions: Permissions::Directory,
+                    target: subdir.compute_hash().into(),
+                });
+                continue;
+            }
+
+            if !keep.contains(&relative) {
+                continue;
+            }
+            let permissions = Permissions::from_metadata(&metadata);
+            if permissions != Permissions::Symlink && is_unsupported_file_type(&metadata) {
+                return Err(SwhidError::UnsupportedFileType(entry_path));
+            }
+            let target = if permissions == Permissions::Symlink {
+                hash_symlink_target(&entry_path)?
+            } else {
+                hash_fn(&entry_path)?
+            };
+            directory.add_entry(DirectoryEntry {
+                name,
+                permissions,
+                target: target.into(),
+            });
+        }
+        Ok(directory)
+    }
+
+    /// Parse a git tree object body (`mode SP name NUL <20 raw bytes>`,
+    /// repeated) into a `Directory`, retaining the original bytes as
+    /// `raw_manifest` so [`SelfConsistent::verify_self_consistent`] can
+    /// detect a non-canonical encoding (e.g. a zero-padded mode string).
+    pub fn from_raw_manifest(bytes: &[u8]) -> Result<Directory, SwhidError> {
+        let mut entries = Vec::new();
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let space = rest
+                .iter()
+                .position(|&b| b == b' ')
+                .ok_or_else(|| SwhidError::InvalidGitObject("missing mode separator".into()))?;
+            let mode = &rest[..space];
+            let permissions = Permissions::from_git_mode_bytes(mode)?;
+
+            let nul = rest[space + 1..]
+                .iter()
+                .position(|&b| b == 0)
+                .ok_or_else(|| SwhidError::InvalidGitObject("missing name terminator".into()))?;
+            let name = rest[space + 1..space + 1 + nul].to_vec();
+
+            let target_start = space + 1 + nul + 1;
+            if rest.len() < target_start + 20 {
+                return Err(SwhidError::InvalidGitObject(
+                    "truncated entry hash".into(),
+                ));
+            }
+            let target: [u8; 20] = rest[target_start..target_start + 20]
+                .try_into()
+                .expect("slice is exactly 20 bytes");
+
+            if entries.iter().any(|e: &DirectoryEntry| e.name == name) {
+                return Err(SwhidError::DuplicateEntry(
+                    String::from_utf8_lossy(&name).into_owned(),
+                ));
+            }
+
+            entries.push(DirectoryEntry {
+                name,
+                permissions,
+                target: target.into(),
+            });
+            rest = &rest[target_start + 20..];
+        }
+
+        Ok(Directory {
+            entries,
+            raw_manifest: Some(bytes.to_vec()),
+        })
+    }
+
+    /// Build a (possibly multi-level) `Directory` from a flat listing of
+    /// `/`-separated relative paths with their already-known permissions
+    /// and content hash, without reading any files. This is what lets a CI
+    /// system reproduce a directory SWHID from a stored manifest (path,
+    /// mode, sha1) alone.
+    pub fn from_listing(
+        entries: impl IntoIterator<Item = (Vec<u8>, Permissions, [u8; 20])>,
+    ) -> Result<Directory, SwhidError> {
+        let mut root: BTreeMap<Vec<u8>, ListingNode> = BTreeMap::new();
+        for (path, permissions, target) in entries {
+            if path.is_empty() {
+                return Err(SwhidError::InvalidGitObject(
+                    "listing contains an empty path".into(),
+                ));
+            }
+            let mut components: Vec<Vec<u8>> =
+                path.split(|&b| b == b'/').map(|c| c.to_vec()).collect();
+            let name = components.pop().expect("path is non-empty");
+            let parent = listing_ensure_dir(&mut root, &components)?;
+            if parent.contains_key(&name) {
+                return Err(SwhidError::DuplicateEntry(
+                    String::from_utf8_lossy(&name).into_owned(),
+                ));
+            }
+            parent.insert(name, ListingNode::File { permissions, target });
+        }
+        Ok(listing_build_directory(&root))
+    }
+}
+
+/// A node in the in-memory tree built up by [`Directory::from_listing`]
+/// before it's folded into nested [`Directory`]s.
+enum ListingNode {
+    File {
+        permissions: Permissions,
+        target: [u8; 20],
+    },
+    Dir(BTreeMap<Vec<u8>, ListingNode>),
+}
+
+fn listing_ensure_dir<'a>(
+    root: &'a mut BTreeMap<Vec<u8>, ListingNode>,
+    components: &[Vec<u8>],
+) -> Result<&'a mut BTreeMap<Vec<u8>, ListingNode>, SwhidError> {
+    let mut current = root;
+    for component in components {
+        let entry = current
+            .entry(component.clone())
+            .or_insert_with(|| ListingNode::Dir(BTreeMap::new()));
+        match entry {
+            ListingNode::Dir(map) => current = map,
+            ListingNode::File { .. } => {
+                return Err(SwhidError::InvalidGitObject(
+                    "listing path treats a file entry as a directory".into(),
+                ))
+            }
+        }
+    }
+    Ok(current)
+}
+
+fn listing_build_directory(node: &BTreeMap<Vec<u8>, ListingNode>) -> Directory {
+    let mut directory = Directory::new();
+    for (name, child) in node {
+        let (permissions, target) = match child {
+            ListingNode::File { permissions, target } => (*permissions, *target),
+            ListingNode::Dir(children) => {
+                let subdir = listing_build_directory(children);
+                (Permissions::Directory, subdir.compute_hash())
+            }
+        };
+        directory.add_entry(DirectoryEntry {
+            name: name.clone(),
+            permissions,
+            target: target.into(),
+        });
+    }
+    directory
+}
+
+/// Per-type entry counts, as returned by [`Directory::counts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EntryCounts {
+    pub files: usize,
+    pub dirs: usize,
+    pub symlinks: usize,
+}
+
+impl Directory {
+    /// Count this directory's immediate entries by type. This is shallow —
+    /// it doesn't recurse into subdirectories, since a `Directory` only
+    /// stores its children's hashes, not their own entries. Submodule
+    /// entries count as `files`: a gitlink points at a commit in another
+    /// repository, so from this tree's point of view there's nothing to
+    /// recurse into.
+    pub fn counts(&self) -> EntryCounts {
+        let mut counts = EntryCounts::default();
+        for entry in &self.entries {
+            match entry.permissions {
+                Permissions::Directory => counts.dirs += 1,
+                Permissions::Symlink => counts.symlinks += 1,
+                Permissions::Regular | Permissions::Executable | Permissions::Submodule => {
+                    counts.files += 1
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// A single difference between two directories' immediate entries, as
+/// returned by [`Directory::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryDiff {
+    Added { name: Vec<u8>, entry: DirectoryEntry },
+    Removed { name: Vec<u8>, entry: DirectoryEntry },
+    Modified {
+        name: Vec<u8>,
+        before: DirectoryEntry,
+        after: DirectoryEntry,
+    },
+}
+
+impl Directory {
+    /// Compare the immediate entries of `self` and `other` by name, without
+    /// recursing into subdirectories. An entry present in only one side is
+    /// `Added`/`Removed`; an entry present in both but with a different
+    /// `target` or `permissions` is `Modified`.
+    pub fn diff(&self, other: &Directory) -> Vec<EntryDiff> {
+        let mut diffs = Vec::new();
+        for before in self.sorted_entries() {
+            match other.entries.iter().find(|e| e.name == before.name) {
+                None => diffs.push(EntryDiff::Removed {
+                    name: before.name.clone(),
+                    entry: before.clone(),
+                }),
+                Some(after) if after != before => diffs.push(EntryDiff::Modified {
+                    name: before.name.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for after in other.sorted_entries() {
+            if !self.entries.iter().any(|e| e.name == after.name) {
+                diffs.push(EntryDiff::Added {
+                    name: after.name.clone(),
+                    entry: after.clone(),
+                });
+            }
+        }
+        diffs
+    }
+}
+
+impl SelfConsistent for Directory {
+    fn verify_self_consistent(&self) -> Result<(), SwhidError> {
+        match &self.raw_manifest {
+            None => Ok(()),
+            Some(raw) => {
+                if *raw == self.manifest() {
+                    Ok(())
+                } else {
+                    Err(SwhidError::InconsistentObject)
+                }
+            }
+        }
+    }
+}
+
+impl crate::git_manifest::GitManifest for Directory {
+    fn git_type(&self) -> &'static str {
+        "tree"
+    }
+
+    fn manifest(&self) -> Vec<u8> {
+        match &self.raw_manifest {
+            Some(raw) => raw.clone(),
+            None => self.manifest(),
+        }
+    }
+
+    fn swhid(&self) -> Swhid {
+        Directory::swhid(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `DirectoryEntry::target` is a [`GitSha1`], not a bare `[u8; 20]` — a
+    /// content hash and a directory hash can't be swapped for one another
+    /// without an explicit (and visible) conversion, and the manifest this
+    /// produces is still byte-identical to what a raw array would hash to.
+    #[test]
+    fn directory_entry_target_is_a_typed_git_sha1_not_a_bare_array() {
+        let content_hash = hash_git_object("blob", b"hello");
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"hello.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: GitSha1::new(content_hash),
+        });
+        assert_eq!(directory.entries()[0].target, GitSha1::from(content_hash));
+        assert_eq!(directory.compute_hash(), hash_git_object(
+            "tree",
+            &[b"100644 hello.txt\0".as_slice(), &content_hash].concat(),
+        ));
+    }
+
+    #[test]
+    fn git_manifest_impl_agrees_with_the_inherent_methods() {
+        use crate::git_manifest::GitManifest;
+
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"foo.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [1u8; 20].into(),
+        });
+
+        assert_eq!(GitManifest::git_type(&directory), "tree");
+        assert_eq!(GitManifest::manifest(&directory), directory.manifest());
+        assert_eq!(GitManifest::swhid(&directory), directory.swhid());
+    }
+
+    #[test]
+    fn compute_swhid_matches_swhid_and_needs_no_mutable_reference() {
+        let directory = Directory::new();
+        assert_eq!(directory.compute_swhid(), directory.swhid());
+    }
+
+    #[test]
+    fn empty_directory_matches_git_empty_tree() {
+        let directory = Directory::new();
+        assert_eq!(
+            hex::encode(directory.compute_hash()),
+            "4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+        );
+    }
+
+    #[test]
+    fn write_manifest_entry_matches_the_entrys_slice_within_the_full_manifest() {
+        let entry = DirectoryEntry {
+            name: b"foo.txt".to_vec(),
+            permissions: Permissions::Executable,
+            target: [9u8; 20].into(),
+        };
+        let mut written = Vec::new();
+        entry.write_manifest_entry(&mut written).unwrap();
+
+        let mut directory = Directory::new();
+        directory.add_entry(entry);
+        assert_eq!(written, directory.manifest());
+    }
+
+    #[test]
+    fn streamed_compute_hash_matches_the_vec_based_manifest_for_a_mixed_directory() {
+        let mut directory = Directory::new();
+        for i in 0..50 {
+            directory.add_entry(DirectoryEntry {
+                name: format!("file-{i}.txt").into_bytes(),
+                permissions: if i % 2 == 0 { Permissions::Regular } else { Permissions::Executable },
+                target: [i as u8; 20].into(),
+            });
+        }
+        directory.add_entry(DirectoryEntry {
+            name: b"subdir".to_vec(),
+            permissions: Permissions::Directory,
+            target: [0xab; 20].into(),
+        });
+        directory.add_entry(DirectoryEntry {
+            name: b"link".to_vec(),
+            permissions: Permissions::Symlink,
+            target: [0xcd; 20].into(),
+        });
+
+        assert_eq!(directory.compute_hash(), hash_git_object("tree", &directory.manifest()));
+    }
+
+    #[test]
+    fn sorts_directories_as_if_slash_terminated() {
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"foo.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [1u8; 20].into(),
+        });
+        directory.add_entry(DirectoryEntry {
+            name: b"foo".to_vec(),
+            permissions: Permissions::Directory,
+            target: [2u8; 20].into(),
+        });
+        let sorted = directory.sorted_entries();
+        // "foo/" > "foo.txt" because '.' (0x2e) < '/' (0x2f)
+        assert_eq!(sorted[0].name, b"foo.txt");
+        assert_eq!(sorted[1].name, b"foo");
+    }
+
+    #[test]
+    fn submodule_entry_uses_160000_mode_in_the_manifest() {
+        assert_eq!(Permissions::from_mode(0o160000), Permissions::Submodule);
+
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"vendor/lib".to_vec(),
+            permissions: Permissions::Submodule,
+            target: [4u8; 20].into(),
+        });
+        let manifest = directory.manifest();
+        assert!(manifest.starts_with(b"160000 vendor/lib\0"));
+    }
+
+    #[test]
+    fn git_tree_sort_key_treats_directories_as_slash_terminated() {
+        let file_key = git_tree_sort_key(b"foo.txt", false);
+        let dir_key = git_tree_sort_key(b"foo", true);
+        assert!(file_key < dir_key);
+    }
+
+    #[test]
+    fn symlink_entry_hashes_target_path_string_not_followed_content() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("target.txt"), b"the real file content").unwrap();
+        std::os::unix::fs::symlink("target.txt", dir.path().join("link")).unwrap();
+
+        let directory =
+            Directory::from_disk_with_hash_fn(dir.path(), &mut |p| {
+                let data = fs::read(p).map_err(|e| SwhidError::io(p, e))?;
+                Ok(hash_git_object("blob", &data))
+            })
+            .unwrap();
+
+        let link_entry = directory
+            .entries()
+            .iter()
+            .find(|e| e.name == b"link")
+            .unwrap();
+        assert_eq!(link_entry.permissions, Permissions::Symlink);
+
+        let expected = hash_git_object("blob", b"target.txt");
+        assert_eq!(link_entry.target, expected.into());
+    }
+
+    /// A minimal in-memory [`FileSystem`], standing in for a remote backend
+    /// (SFTP, rsync, ...) that [`Directory::from_fs_filtered`] should be
+    /// able to walk without ever touching local disk.
+    struct InMemoryFileSystem {
+        files: BTreeMap<std::path::PathBuf, Vec<u8>>,
+        symlinks: BTreeMap<std::path::PathBuf, std::path::PathBuf>,
+        dirs: Vec<std::path::PathBuf>,
+    }
+
+    impl FileSystem for InMemoryFileSystem {
+        fn read_dir(&self, path: &Path) -> Result<Vec<std::path::PathBuf>, SwhidError> {
+            let mut children: Vec<std::path::PathBuf> = self
+                .files
+                .keys()
+                .chain(self.symlinks.keys())
+                .chain(self.dirs.iter())
+                .filter(|p| p.parent() == Some(path))
+                .cloned()
+                .collect();
+            children.sort();
+            children.dedup();
+            Ok(children)
+        }
+
+        fn metadata(&self, path: &Path) -> Result<Permissions, SwhidError> {
+            if self.dirs.contains(&path.to_path_buf()) {
+                Ok(Permissions::Directory)
+            } else if self.symlinks.contains_key(path) {
+                Ok(Permissions::Symlink)
+            } else if self.files.contains_key(path) {
+                Ok(Permissions::Regular)
+            } else {
+                Err(SwhidError::io(path, std::io::Error::from(std::io::ErrorKind::NotFound)))
+            }
+        }
+
+        fn read_file(&self, path: &Path) -> Result<Vec<u8>, SwhidError> {
+            self.files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| SwhidError::io(path, std::io::Error::from(std::io::ErrorKind::NotFound)))
+        }
+
+        fn read_link(&self, path: &Path) -> Result<std::path::PathBuf, SwhidError> {
+            self.symlinks
+                .get(path)
+                .cloned()
+                .ok_or_else(|| SwhidError::io(path, std::io::Error::from(std::io::ErrorKind::NotFound)))
+        }
+    }
+
+    #[test]
+    fn from_fs_filtered_walks_an_in_memory_backend_matching_local_disk() {
+        let root = Path::new("/remote");
+        let fs_backend = InMemoryFileSystem {
+            files: BTreeMap::from([(root.join("hello.txt"), b"hello".to_vec())]),
+            symlinks: BTreeMap::from([(root.join("link"), std::path::PathBuf::from("hello.txt"))]),
+            dirs: vec![root.to_path_buf()],
+        };
+
+        let directory = Directory::from_fs_filtered(
+            &fs_backend,
+            root,
+            &mut |p| Ok(hash_git_object("blob", &fs_backend.read_file(p)?)),
+            &|_| false,
+            &|_, permissions| permissions,
+        )
+        .unwrap();
+
+        let file_entry = directory.entries().iter().find(|e| e.name == b"hello.txt").unwrap();
+        assert_eq!(file_entry.target, hash_git_object("blob", b"hello").into());
+
+        let link_entry = directory.entries().iter().find(|e| e.name == b"link").unwrap();
+        assert_eq!(link_entry.permissions, Permissions::Symlink);
+        assert_eq!(link_entry.target, hash_git_object("blob", b"hello.txt").into());
+
+        // Same tree on local disk hashes identically.
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("hello.txt"), b"hello").unwrap();
+        std::os::unix::fs::symlink("hello.txt", dir.path().join("link")).unwrap();
+        let on_disk = Directory::from_disk_with_hash_fn(dir.path(), &mut |p| {
+            Ok(hash_git_object("blob", &fs::read(p).map_err(|e| SwhidError::io(p, e))?))
+        })
+        .unwrap();
+        assert_eq!(directory.compute_hash(), on_disk.compute_hash());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symlink_to_an_executable_target_still_gets_symlink_mode_not_executable_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("run.sh");
+        fs::write(&target, b"#!/bin/sh\n").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("run.sh", dir.path().join("link")).unwrap();
+
+        let directory = Directory::from_disk_with_hash_fn(dir.path(), &mut |p| {
+            let data = fs::read(p).map_err(|e| SwhidError::io(p, e))?;
+            Ok(hash_git_object("blob", &data))
+        })
+        .unwrap();
+
+        let target_entry = directory.entries().iter().find(|e| e.name == b"run.sh").unwrap();
+        assert_eq!(target_entry.permissions, Permissions::Executable);
+        assert_eq!(target_entry.permissions.git_mode(), "100755");
+
+        let link_entry = directory.entries().iter().find(|e| e.name == b"link").unwrap();
+        assert_eq!(link_entry.permissions, Permissions::Symlink);
+        assert_eq!(link_entry.permissions.git_mode(), "120000");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn from_disk_rejects_a_fifo_instead_of_hanging_on_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let fifo = dir.path().join("pipe");
+        assert!(std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap()
+            .success());
+
+        let result = Directory::from_disk_with_hash_fn(dir.path(), &mut |p| {
+            let data = fs::read(p).map_err(|e| SwhidError::io(p, e))?;
+            Ok(hash_git_object("blob", &data))
+        });
+        assert!(matches!(result, Err(SwhidError::UnsupportedFileType(path)) if path == fifo));
+    }
+
+    #[test]
+    fn from_disk_with_allowlist_drops_entries_and_empty_directories_not_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("tracked.txt"), b"tracked").unwrap();
+        fs::write(dir.path().join("untracked.txt"), b"untracked").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/tracked_nested.txt"), b"nested").unwrap();
+        fs::write(dir.path().join("sub/untracked_nested.txt"), b"also untracked").unwrap();
+        fs::create_dir(dir.path().join("empty_after_filtering")).unwrap();
+        fs::write(
+            dir.path().join("empty_after_filtering/untracked.txt"),
+            b"dropped",
+        )
+        .unwrap();
+
+        let keep: std::collections::HashSet<Vec<u8>> = [
+            b"tracked.txt".to_vec(),
+            b"sub/tracked_nested.txt".to_vec(),
+        ]
+        .into_iter()
+        .collect();
+
+        let directory = Directory::from_disk_with_allowlist(dir.path(), &keep, &mut |p| {
+            let data = fs::read(p).map_err(|e| SwhidError::io(p, e))?;
+            Ok(hash_git_object("blob", &data))
+        })
+        .unwrap();
+
+        let mut names: Vec<&[u8]> = directory
+            .entries()
+            .iter()
+            .map(|e| e.name.as_slice())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![b"sub".as_slice(), b"tracked.txt".as_slice()]);
+
+        let sub_hash = directory
+            .entries()
+            .iter()
+            .find(|e| e.name == b"sub")
+            .unwrap()
+            .target;
+        let expected_sub = {
+            let mut sub = Directory::new();
+            sub.add_entry(DirectoryEntry {
+                name: b"tracked_nested.txt".to_vec(),
+                permissions: Permissions::Regular,
+                target: hash_git_object("blob", b"nested").into(),
+            });
+            sub.compute_hash()
+        };
+        assert_eq!(sub_hash, expected_sub.into());
+    }
+
+    #[test]
+    fn canonically_built_directory_is_self_consistent() {
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"file.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [7u8; 20].into(),
+        });
+        assert!(directory.verify_self_consistent().is_ok());
+    }
+
+    #[test]
+    fn non_canonical_mode_is_flagged_inconsistent() {
+        let mut raw = Vec::new();
+        // Zero-padded "040000" instead of the canonical "40000".
+        raw.extend_from_slice(b"040000 sub\0");
+        raw.extend_from_slice(&[9u8; 20]);
+        let directory = Directory::from_raw_manifest(&raw).unwrap();
+        assert!(directory.verify_self_consistent().is_err());
+    }
+
+    #[test]
+    fn canonical_raw_manifest_is_self_consistent() {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(b"100644 file.txt\0");
+        raw.extend_from_slice(&[3u8; 20]);
+        let directory = Directory::from_raw_manifest(&raw).unwrap();
+        assert!(directory.verify_self_consistent().is_ok());
+    }
+
+    #[test]
+    fn with_raw_manifest_overrides_regenerated_bytes() {
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"file.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [7u8; 20].into(),
+        });
+        let from_entries = directory.compute_hash();
+
+        let mut quirky_raw = Vec::new();
+        quirky_raw.extend_from_slice(b"100644 other.txt\0");
+        quirky_raw.extend_from_slice(&[9u8; 20]);
+        let with_raw = directory.with_raw_manifest(quirky_raw.clone());
+        assert_eq!(with_raw.compute_hash(), hash_git_object("tree", &quirky_raw));
+        assert_ne!(with_raw.compute_hash(), from_entries);
+        assert!(with_raw.verify_self_consistent().is_err());
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_modified_entries() {
+        let mut before = Directory::new();
+        before.add_entry(DirectoryEntry {
+            name: b"unchanged.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [1u8; 20].into(),
+        });
+        before.add_entry(DirectoryEntry {
+            name: b"removed.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [2u8; 20].into(),
+        });
+        before.add_entry(DirectoryEntry {
+            name: b"changed.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [3u8; 20].into(),
+        });
+
+        let mut after = Directory::new();
+        after.add_entry(DirectoryEntry {
+            name: b"unchanged.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [1u8; 20].into(),
+        });
+        after.add_entry(DirectoryEntry {
+            name: b"changed.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [4u8; 20].into(),
+        });
+        after.add_entry(DirectoryEntry {
+            name: b"added.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [5u8; 20].into(),
+        });
+
+        let mut diffs = before.diff(&after);
+        diffs.sort_by(|a, b| diff_name(a).cmp(diff_name(b)));
+
+        assert_eq!(diffs.len(), 3);
+        assert!(matches!(&diffs[0], EntryDiff::Added { name, .. } if name == b"added.txt"));
+        assert!(matches!(&diffs[1], EntryDiff::Modified { name, .. } if name == b"changed.txt"));
+        assert!(matches!(&diffs[2], EntryDiff::Removed { name, .. } if name == b"removed.txt"));
+    }
+
+    fn diff_name(diff: &EntryDiff) -> &[u8] {
+        match diff {
+            EntryDiff::Added { name, .. } => name,
+            EntryDiff::Removed { name, .. } => name,
+            EntryDiff::Modified { name, .. } => name,
+        }
+    }
+
+    #[test]
+    fn from_listing_builds_nested_directories_without_touching_disk() {
+        let directory = Directory::from_listing(vec![
+            (b"README.md".to_vec(), Permissions::Regular, [1u8; 20]),
+            (b"src/main.rs".to_vec(), Permissions::Regular, [2u8; 20]),
+            (b"src/bin/run.sh".to_vec(), Permissions::Executable, [3u8; 20]),
+        ])
+        .unwrap();
+
+        let mut expected_src = Directory::new();
+        expected_src.add_entry(DirectoryEntry {
+            name: b"main.rs".to_vec(),
+            permissions: Permissions::Regular,
+            target: [2u8; 20].into(),
+        });
+        let mut expected_bin = Directory::new();
+        expected_bin.add_entry(DirectoryEntry {
+            name: b"run.sh".to_vec(),
+            permissions: Permissions::Executable,
+            target: [3u8; 20].into(),
+        });
+        expected_src.add_entry(DirectoryEntry {
+            name: b"bin".to_vec(),
+            permissions: Permissions::Directory,
+            target: expected_bin.compute_hash().into(),
+        });
+        let mut expected_root = Directory::new();
+        expected_root.add_entry(DirectoryEntry {
+            name: b"README.md".to_vec(),
+            permissions: Permissions::Regular,
+            target: [1u8; 20].into(),
+        });
+        expected_root.add_entry(DirectoryEntry {
+            name: b"src".to_vec(),
+            permissions: Permissions::Directory,
+            target: expected_src.compute_hash().into(),
+        });
+
+        assert_eq!(directory.swhid(), expected_root.swhid());
+    }
+
+    #[test]
+    fn from_listing_rejects_two_entries_with_the_same_name() {
+        let result = Directory::from_listing(vec![
+            (b"a.txt".to_vec(), Permissions::Regular, [1u8; 20]),
+            (b"a.txt".to_vec(), Permissions::Executable, [2u8; 20]),
+        ]);
+        assert!(matches!(result, Err(SwhidError::DuplicateEntry(name)) if name == "a.txt"));
+    }
+
+    #[test]
+    fn from_raw_manifest_rejects_two_entries_with_the_same_name() {
+        let mut manifest = Vec::new();
+        manifest.extend_from_slice(b"100644 a.txt\0");
+        manifest.extend_from_slice(&[1u8; 20]);
+        manifest.extend_from_slice(b"100755 a.txt\0");
+        manifest.extend_from_slice(&[2u8; 20]);
+
+        let result = Directory::from_raw_manifest(&manifest);
+        assert!(matches!(result, Err(SwhidError::DuplicateEntry(name)) if name == "a.txt"));
+    }
+
+    #[test]
+    fn from_listing_rejects_a_path_that_treats_a_file_as_a_directory() {
+        let result = Directory::from_listing(vec![
+            (b"a".to_vec(), Permissions::Regular, [1u8; 20]),
+            (b"a/b".to_vec(), Permissions::Regular, [2u8; 20]),
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn counts_tallies_entries_by_type() {
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"file.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [1u8; 20].into(),
+        });
+        directory.add_entry(DirectoryEntry {
+            name: b"run.sh".to_vec(),
+            permissions: Permissions::Executable,
+            target: [2u8; 20].into(),
+        });
+        directory.add_entry(DirectoryEntry {
+            name: b"link".to_vec(),
+            permissions: Permissions::Symlink,
+            target: [3u8; 20].into(),
+        });
+        directory.add_entry(DirectoryEntry {
+            name: b"sub".to_vec(),
+            permissions: Permissions::Directory,
+            target: [4u8; 20].into(),
+        });
+        directory.add_entry(DirectoryEntry {
+            name: b"vendor".to_vec(),
+            permissions: Permissions::Submodule,
+            target: [5u8; 20].into(),
+        });
+
+        assert_eq!(
+            directory.counts(),
+            EntryCounts {
+                files: 3,
+                dirs: 1,
+                symlinks: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn diff_is_empty_for_identical_directories() {
+        let mut directory = Directory::new();
+        directory.add_entry(DirectoryEntry {
+            name: b"file.txt".to_vec(),
+            permissions: Permissions::Regular,
+            target: [7u8; 20].into(),
+        });
+        assert!(directory.diff(&directory.clone()).is_empty());
+    }
+}
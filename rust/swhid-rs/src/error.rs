@@ -0,0 +1,109 @@
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+/// Errors that can occur while computing or parsing SWHIDs.
+///
+/// The filesystem-flavored variants ([`SwhidError::Io`],
+/// [`SwhidError::NotADirectory`], [`SwhidError::NotFound`],
+/// [`SwhidError::FileTooLarge`], [`SwhidError::LengthMismatch`]) only exist
+/// with the `std` feature enabled, since they carry
+/// `std::path::PathBuf`/`std::io::Error`. The pure hashing and parsing
+/// variants stay available without `std`.
+#[derive(Debug, thiserror::Error)]
+pub enum SwhidError {
+    #[cfg(feature = "std")]
+    #[error("I/O error at {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid SWHID string: {0}")]
+    InvalidSwhid(String),
+
+    /// Like [`SwhidError::InvalidSwhid`], but specifically for a hash part
+    /// that failed to parse as hex, with the index of the first offending
+    /// character so editor integrations can underline it.
+    #[error("invalid hex in SWHID hash {input:?} at position {position}")]
+    InvalidHash { input: String, position: usize },
+
+    #[cfg(feature = "std")]
+    #[error("path is not a directory: {0}")]
+    NotADirectory(PathBuf),
+
+    #[cfg(feature = "std")]
+    #[error("path does not exist: {0}")]
+    NotFound(PathBuf),
+
+    #[cfg(feature = "std")]
+    #[error("file too large to hash: {path} is {size} bytes")]
+    FileTooLarge { path: PathBuf, size: u64 },
+
+    #[cfg(feature = "std")]
+    #[error("expected {expected} bytes from {path}, but read {actual}")]
+    LengthMismatch {
+        path: PathBuf,
+        expected: u64,
+        actual: u64,
+    },
+
+    #[error("invalid git object: {0}")]
+    InvalidGitObject(String),
+
+    /// Two directory entries share identical name bytes, which git's tree
+    /// format forbids (and which would make the manifest ambiguous to
+    /// encode, since entries are keyed by name).
+    #[error("duplicate directory entry name: {0:?}")]
+    DuplicateEntry(String),
+
+    #[error("object is not self-consistent: recomputing its canonical form does not reproduce the parsed bytes")]
+    InconsistentObject,
+
+    /// A directory entry that's neither a regular file, directory nor
+    /// symlink (a FIFO, Unix domain socket, or block/char device). These
+    /// have no defined git object representation, and opening one to hash
+    /// its "content" risks blocking forever (a FIFO with no writer) or
+    /// reading device-specific garbage.
+    #[cfg(feature = "std")]
+    #[error("unsupported file type (not a regular file, directory or symlink): {0}")]
+    UnsupportedFileType(PathBuf),
+}
+
+#[cfg(feature = "std")]
+impl SwhidError {
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        SwhidError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn io_variant_source_downcasts_to_the_wrapped_io_error() {
+        let io_error = io::Error::new(io::ErrorKind::NotFound, "no such file");
+        let err = SwhidError::io("missing.txt", io_error);
+
+        let source = err.source().expect("Io variant should report a source");
+        let downcast = source
+            .downcast_ref::<io::Error>()
+            .expect("source should downcast to io::Error");
+        assert_eq!(downcast.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn non_io_variant_has_no_source() {
+        assert!(SwhidError::InvalidSwhid("bogus".into()).source().is_none());
+    }
+}
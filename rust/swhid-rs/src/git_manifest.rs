@@ -0,0 +1,21 @@
+use crate::swhid::Swhid;
+
+/// Implemented by every node type (`Directory`, `Revision`, `Release`,
+/// `Snapshot`) that hashes as a git object, so generic code can serialize
+/// or hash any of them uniformly without matching on the concrete type.
+/// Mostly plumbing over each type's existing `to_git_object`/`to_manifest`
+/// and `compute_hash`/`swhid` methods.
+pub trait GitManifest {
+    /// The git object type (`"tree"`, `"commit"`, `"tag"`) this node hashes
+    /// as. Snapshots have no git equivalent, so they use `"snapshot"`,
+    /// matching [`crate::hash::hash_git_object`]'s `git_type` argument
+    /// elsewhere in this crate.
+    fn git_type(&self) -> &'static str;
+
+    /// The exact bytes hashed to produce [`GitManifest::swhid`]: `raw_manifest`
+    /// when the node was parsed from non-canonical bytes, otherwise the
+    /// freshly regenerated canonical manifest.
+    fn manifest(&self) -> Vec<u8>;
+
+    fn swhid(&self) -> Swhid;
+}
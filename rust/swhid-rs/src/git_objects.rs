@@ -0,0 +1,70 @@
+//! Header parsing for raw (inflated) git object buffers, shared by the
+//! loose ([`crate::loose`]) and packed ([`crate::pack`]) object readers.
+
+#[cfg(not(feature = "std"))]
+use alloc::format;
+
+use crate::error::SwhidError;
+use crate::swhid::ObjectType;
+
+/// Read the leading `<type> SP <len> NUL` header off `bytes` — the way git
+/// writes it for loose objects, and the way a pack entry's header is
+/// reconstructed once its type and size are known — and return the mapped
+/// [`ObjectType`] plus the header's length in bytes, so the caller can
+/// slice `&bytes[header_len..]` for the body.
+///
+/// Doesn't validate `len` against the remaining bytes: what "remaining"
+/// means differs between a loose object (the rest of the inflated buffer)
+/// and a pack entry (already known from the entry header), so that check
+/// is left to the caller.
+pub fn sniff_type(bytes: &[u8]) -> Result<(ObjectType, usize), SwhidError> {
+    let nul = bytes
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or_else(|| SwhidError::InvalidGitObject("missing header terminator".into()))?;
+    let header = core::str::from_utf8(&bytes[..nul])
+        .map_err(|_| SwhidError::InvalidGitObject("non-UTF-8 object header".into()))?;
+    let (type_label, _len) = header.split_once(' ').ok_or_else(|| {
+        SwhidError::InvalidGitObject(format!("malformed object header {header:?}"))
+    })?;
+    let object_type = match type_label {
+        "blob" => ObjectType::Content,
+        "tree" => ObjectType::Directory,
+        "commit" => ObjectType::Revision,
+        "tag" => ObjectType::Release,
+        other => {
+            return Err(SwhidError::InvalidGitObject(format!(
+                "unknown object type: {other:?}"
+            )))
+        }
+    };
+    Ok((object_type, nul + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniff_type_maps_every_known_git_type() {
+        assert_eq!(sniff_type(b"blob 5\0hello").unwrap(), (ObjectType::Content, 7));
+        assert_eq!(sniff_type(b"tree 0\0").unwrap(), (ObjectType::Directory, 7));
+        assert_eq!(sniff_type(b"commit 3\0abc").unwrap(), (ObjectType::Revision, 9));
+        assert_eq!(sniff_type(b"tag 3\0abc").unwrap(), (ObjectType::Release, 6));
+    }
+
+    #[test]
+    fn sniff_type_rejects_an_unknown_type() {
+        assert!(sniff_type(b"snapshot 0\0").is_err());
+    }
+
+    #[test]
+    fn sniff_type_rejects_a_missing_terminator() {
+        assert!(sniff_type(b"blob 5 no-nul-here").is_err());
+    }
+
+    #[test]
+    fn sniff_type_rejects_a_missing_space() {
+        assert!(sniff_type(b"blob5\0").is_err());
+    }
+}
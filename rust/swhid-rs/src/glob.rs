@@ -0,0 +1,93 @@
+//! A minimal shell-style glob matcher (`*` and `?` only), just enough to
+//! support `.swhignore`/exclude patterns without pulling in a dependency.
+
+/// Match `name` against `pattern`, where `*` matches any run of characters
+/// (including none) and `?` matches exactly one character.
+pub(crate) fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    match_from(&pattern, &name)
+}
+
+/// Dynamic-programming glob matcher: `dp[j]` holds whether the pattern
+/// prefix processed so far matches `name[..j]`, and each pattern character
+/// advances `dp` to the next row in place of recursing. This is
+/// `O(pattern.len() * name.len())` time, `O(name.len())` space — the naive
+/// recursive backtracker this replaced was exponential on adversarial
+/// patterns (e.g. a ~30-char run of repeated `*a` segments against a
+/// non-matching name of the same length took seconds, growing
+/// exponentially per segment added), and `.swhignore`/force-executable
+/// patterns are read from the directory being hashed, so an untrusted
+/// archive can supply one.
+fn match_from(pattern: &[char], name: &[char]) -> bool {
+    let n = name.len();
+    let mut dp = vec![false; n + 1];
+    dp[0] = true;
+
+    for &p in pattern {
+        let mut next = vec![false; n + 1];
+        match p {
+            '*' => {
+                next[0] = dp[0];
+                for j in 1..=n {
+                    next[j] = next[j - 1] || dp[j];
+                }
+            }
+            '?' => {
+                next[1..=n].copy_from_slice(&dp[..n]);
+            }
+            c => {
+                for j in 1..=n {
+                    next[j] = dp[j - 1] && name[j - 1] == c;
+                }
+            }
+        }
+        dp = next;
+    }
+
+    dp[n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_matches_any_run() {
+        assert!(glob_match("*.bak", "file.bak"));
+        assert!(glob_match("*.bak", ".bak"));
+        assert!(!glob_match("*.bak", "file.txt"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_char() {
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "ac"));
+        assert!(!glob_match("a?c", "abbc"));
+    }
+
+    #[test]
+    fn exact_match_with_no_wildcards() {
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exacted"));
+    }
+
+    #[test]
+    fn adversarial_repeated_star_segments_resolve_quickly() {
+        // A pattern of repeated `*a` segments against a same-length name
+        // with no `a` in it at all: exponential for the old recursive
+        // backtracker (seconds at ~30 chars), linear-ish for the DP
+        // matcher. This must return promptly either way.
+        let pattern = "*a".repeat(30);
+        let name = "b".repeat(60);
+        assert!(!glob_match(&pattern, &name));
+    }
+
+    #[test]
+    fn star_sequences_still_match_correctly() {
+        assert!(glob_match(&"*a".repeat(10), &format!("{}a", "a".repeat(9))));
+        assert!(glob_match("**", "anything"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+    }
+}
@@ -0,0 +1,71 @@
+//! Compute Software Heritage persistent identifiers (SWHIDs) for files,
+//! directories and other git-shaped objects.
+//!
+//! With `default-features = false, features = ["no_std_core"]`, only the
+//! pure git-object hashing core (this module, [`hash`], [`swhid`] and
+//! [`Content::from_data`]/[`Content::from_git_blob`]/[`Content::from_range`])
+//! is built, for use on targets without a filesystem (wasm, embedded). The
+//! filesystem-touching modules ([`directory`], [`computer`], [`glob`] and
+//! [`Content::from_file`]) require the `std` feature, which is on by default.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod computer;
+pub mod content;
+#[cfg(feature = "std")]
+pub mod directory;
+pub mod error;
+#[cfg(feature = "std")]
+mod glob;
+#[cfg(feature = "std")]
+pub mod git_manifest;
+pub mod git_objects;
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod loose;
+#[cfg(feature = "std")]
+pub mod pack;
+#[cfg(feature = "std")]
+pub mod revision;
+pub mod swhid;
+#[cfg(feature = "std")]
+pub mod verify;
+
+#[cfg(feature = "std")]
+pub use computer::{
+    CheckoutSwhids, ContentCache, IdentifiedObject, InMemoryContentCache, LsTreeEntry,
+    SwhidComputer, SwhidComputerBuilder, TraversalStats, TraversalSummary, TreeObject,
+};
+pub use content::{compute_content_swhid_str, Content};
+#[cfg(feature = "std")]
+pub use content::LineEnding;
+#[cfg(feature = "std")]
+pub use content::swhid_of_reader;
+#[cfg(feature = "std")]
+pub use directory::{
+    git_tree_entry_cmp, git_tree_sort_key, Directory, DirectoryEntry, EntryCounts, EntryDiff,
+    FileSystem, LocalFileSystem, Permissions,
+};
+pub use error::SwhidError;
+#[cfg(feature = "std")]
+pub use git_manifest::GitManifest;
+pub use git_objects::sniff_type;
+pub use hash::{hash_git_object, hash_git_object_with, DefaultSha1, GitHasher, GitSha1, Sha1Backend};
+#[cfg(feature = "blake2")]
+pub use hash::blake2s_256;
+#[cfg(feature = "std")]
+pub use loose::iter_loose_objects;
+#[cfg(feature = "std")]
+pub use pack::iter_pack_objects;
+#[cfg(feature = "std")]
+pub use revision::{
+    infer_ref_target_type, GitTimestamp, Person, Release, Revision, RevisionBuilder, Snapshot,
+    SnapshotBranch,
+};
+pub use swhid::{ObjectType, QualifiedSwhid, Swhid, SwhidComponents};
+#[cfg(feature = "std")]
+pub use verify::SelfConsistent;
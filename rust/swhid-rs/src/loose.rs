@@ -0,0 +1,227 @@
+//! Walking and decoding loose git objects under a `.git/objects` directory
+//! (as opposed to [`crate::pack`], which decodes packed objects).
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::error::SwhidError;
+use crate::git_objects::sniff_type;
+use crate::hash::hash_git_object;
+use crate::swhid::Swhid;
+
+/// Inflate the loose object at `path`, parse its `<type> SP <len> NUL`
+/// header via [`sniff_type`], and recompute its SWHID from the body —
+/// checking it against `claimed_sha` (the sha1 implied by the object's
+/// fan-out path), which catches bit-rot or a mismatched filename.
+fn decode_loose_object(path: &Path, claimed_sha: [u8; 20]) -> Result<(Swhid, Vec<u8>), SwhidError> {
+    let compressed = fs::read(path).map_err(|e| SwhidError::io(path, e))?;
+    let mut decoder = flate2::read::ZlibDecoder::new(&compressed[..]);
+    let mut decoded = Vec::new();
+    decoder
+        .read_to_end(&mut decoded)
+        .map_err(|e| SwhidError::io(path, e))?;
+
+    let (object_type, header_len) = sniff_type(&decoded)
+        .map_err(|e| SwhidError::InvalidGitObject(format!("loose object {path:?}: {e}")))?;
+    let len_str = std::str::from_utf8(&decoded[..header_len - 1])
+        .ok()
+        .and_then(|header| header.split_once(' '))
+        .map(|(_, len)| len)
+        .ok_or_else(|| {
+            SwhidError::InvalidGitObject(format!("loose object {path:?} has a malformed header"))
+        })?;
+    let declared_len: usize = len_str.parse().map_err(|_| {
+        SwhidError::InvalidGitObject(format!(
+            "loose object {path:?} has a non-numeric length {len_str:?}"
+        ))
+    })?;
+    let body = decoded[header_len..].to_vec();
+    if body.len() != declared_len {
+        return Err(SwhidError::InvalidGitObject(format!(
+            "loose object {path:?} declared {declared_len} bytes but decoded to {}",
+            body.len()
+        )));
+    }
+
+    let git_type = object_type
+        .git_type()
+        .expect("loose objects never decode to Snapshot");
+    let hash = hash_git_object(git_type, &body);
+    if hash != claimed_sha {
+        return Err(SwhidError::InvalidGitObject(format!(
+            "loose object {path:?} hashes to {}, not the {} its path claims",
+            hex::encode(hash),
+            hex::encode(claimed_sha),
+        )));
+    }
+    Ok((Swhid::new(object_type, hash), body))
+}
+
+fn is_hex_of_len(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn collect_loose_object_paths(objects_dir: &Path) -> Result<Vec<(PathBuf, [u8; 20])>, SwhidError> {
+    let mut entries = Vec::new();
+    for fanout in fs::read_dir(objects_dir).map_err(|e| SwhidError::io(objects_dir, e))? {
+        let fanout = fanout.map_err(|e| SwhidError::io(objects_dir, e))?;
+        let Some(prefix) = fanout.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if !is_hex_of_len(&prefix, 2) {
+            // Not a fan-out directory: `pack/`, `info/`, loose-archive
+            // files, etc.
+            continue;
+        }
+        if !fanout
+            .file_type()
+            .map_err(|e| SwhidError::io(fanout.path(), e))?
+            .is_dir()
+        {
+            continue;
+        }
+        for file in fs::read_dir(fanout.path()).map_err(|e| SwhidError::io(fanout.path(), e))? {
+            let file = file.map_err(|e| SwhidError::io(fanout.path(), e))?;
+            let Some(suffix) = file.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            if !is_hex_of_len(&suffix, 38) {
+                continue;
+            }
+            let Ok(sha_bytes) = hex::decode(format!("{prefix}{suffix}")) else {
+                continue;
+            };
+            let Ok(sha) = sha_bytes.try_into() else {
+                continue;
+            };
+            entries.push((file.path(), sha));
+        }
+    }
+    Ok(entries)
+}
+
+enum LooseObjectsIter {
+    Entries(std::vec::IntoIter<(PathBuf, [u8; 20])>),
+    Failed(Option<SwhidError>),
+}
+
+impl Iterator for LooseObjectsIter {
+    type Item = Result<(Swhid, Vec<u8>), SwhidError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            LooseObjectsIter::Entries(iter) => iter
+                .next()
+                .map(|(path, sha)| decode_loose_object(&path, sha)),
+            LooseObjectsIter::Failed(error) => error.take().map(Err),
+        }
+    }
+}
+
+/// Walk a git `objects/` directory (the `xx/yyyy...` sha1 fan-out, skipping
+/// `pack/`, `info/` and anything else that isn't a 2-hex-digit directory),
+/// zlib-inflating and parsing each loose object found, and yield its
+/// [`Swhid`] paired with its inflated body (everything after the
+/// `<type> SP <len> NUL` header). The SWHID is recomputed from the body and
+/// checked against the sha1 implied by the object's fan-out path, so a
+/// mismatch (corruption, or a loose object that was tampered with) surfaces
+/// as an `Err` instead of silently being returned.
+///
+/// Errors reading `objects_dir` itself are reported as a single `Err` item.
+pub fn iter_loose_objects(
+    objects_dir: &Path,
+) -> impl Iterator<Item = Result<(Swhid, Vec<u8>), SwhidError>> {
+    match collect_loose_object_paths(objects_dir) {
+        Ok(entries) => LooseObjectsIter::Entries(entries.into_iter()),
+        Err(error) => LooseObjectsIter::Failed(Some(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swhid::ObjectType;
+    use std::io::Write;
+
+    fn write_loose_object(objects_dir: &Path, type_label: &str, body: &[u8]) -> [u8; 20] {
+        let hash = hash_git_object(type_label, body);
+        let sha_hex = hex::encode(hash);
+        let dir = objects_dir.join(&sha_hex[0..2]);
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut header = format!("{type_label} {}\0", body.len()).into_bytes();
+        header.extend_from_slice(body);
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&header).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        fs::write(dir.join(&sha_hex[2..]), compressed).unwrap();
+        hash
+    }
+
+    #[test]
+    fn iter_loose_objects_yields_every_object_with_matching_swhids() {
+        let dir = tempfile::tempdir().unwrap();
+        let objects_dir = dir.path();
+        let blob_hash = write_loose_object(objects_dir, "blob", b"hello world");
+        let tree_hash = write_loose_object(objects_dir, "tree", b"");
+        // A sibling that isn't a fan-out dir should be ignored.
+        fs::create_dir(objects_dir.join("pack")).unwrap();
+        fs::write(objects_dir.join("pack").join("not-an-object"), b"ignored").unwrap();
+
+        let mut found: Vec<_> = iter_loose_objects(objects_dir)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        found.sort_by_key(|(swhid, _)| swhid.to_string());
+
+        assert_eq!(found.len(), 2);
+        assert!(found
+            .iter()
+            .any(|(swhid, body)| *swhid == Swhid::new(ObjectType::Content, blob_hash)
+                && body == b"hello world"));
+        assert!(found
+            .iter()
+            .any(|(swhid, body)| *swhid == Swhid::new(ObjectType::Directory, tree_hash)
+                && body.is_empty()));
+    }
+
+    #[test]
+    fn iter_loose_objects_reports_a_sha1_mismatch_as_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let objects_dir = dir.path();
+        let real_hash = write_loose_object(objects_dir, "blob", b"hello world");
+        let real_hex = hex::encode(real_hash);
+
+        // Corrupt the stored object by swapping in different content under
+        // the same claimed sha1.
+        let mut header = b"blob 5\0wrong".to_vec();
+        header.truncate(b"blob 5\0".len() + 5);
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &header).unwrap();
+        let compressed = encoder.finish().unwrap();
+        fs::write(
+            objects_dir.join(&real_hex[0..2]).join(&real_hex[2..]),
+            compressed,
+        )
+        .unwrap();
+
+        let results: Vec<_> = iter_loose_objects(objects_dir).collect();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            &results[0],
+            Err(SwhidError::InvalidGitObject(message)) if message.contains("hashes to")
+        ));
+    }
+
+    #[test]
+    fn iter_loose_objects_reports_a_missing_objects_dir_as_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("does-not-exist");
+        let results: Vec<_> = iter_loose_objects(&missing).collect();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(&results[0], Err(SwhidError::Io { .. })));
+    }
+}
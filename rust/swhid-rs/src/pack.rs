@@ -0,0 +1,460 @@
+//! Decoding git packfiles (`.git/objects/pack/*.pack`) into their
+//! constituent objects, for indexing a mirror without unpacking it to loose
+//! objects first.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek, SeekFrom};
+
+use flate2::read::ZlibDecoder;
+
+use crate::error::SwhidError;
+use crate::hash::hash_git_object;
+use crate::swhid::ObjectType;
+
+const OBJ_COMMIT: u8 = 1;
+const OBJ_TREE: u8 = 2;
+const OBJ_BLOB: u8 = 3;
+const OBJ_TAG: u8 = 4;
+const OBJ_OFS_DELTA: u8 = 6;
+const OBJ_REF_DELTA: u8 = 7;
+
+/// Upper bound on a single git object's declared (decompressed) size that
+/// we'll trust enough to pre-allocate for. A corrupt or adversarial pack
+/// can claim an arbitrary 64-bit size in a header varint with no relation
+/// to the bytes that actually follow; without a cap, `Vec::with_capacity`
+/// on that size aborts the whole process via the allocator's
+/// out-of-memory handler — not an ordinary panic a caller could catch —
+/// instead of this function returning a [`SwhidError`] like every other
+/// malformed-input case here does. No real git object is anywhere near
+/// this size.
+const MAX_TRUSTED_OBJECT_SIZE: u64 = 4 * 1024 * 1024 * 1024;
+
+fn git_label(object_type: ObjectType) -> &'static str {
+    match object_type {
+        ObjectType::Content => "blob",
+        ObjectType::Directory => "tree",
+        ObjectType::Revision => "commit",
+        ObjectType::Release => "tag",
+        ObjectType::Snapshot => unreachable!("packfiles never contain snapshot objects"),
+    }
+}
+
+enum RawObject {
+    Base { object_type: ObjectType, data: Vec<u8> },
+    OfsDelta { base_offset: u64, data: Vec<u8> },
+    RefDelta { base_sha1: [u8; 20], data: Vec<u8> },
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, SwhidError> {
+    let mut byte = [0u8; 1];
+    reader
+        .read_exact(&mut byte)
+        .map_err(|e| SwhidError::InvalidGitObject(format!("truncated pack: {e}")))?;
+    Ok(byte[0])
+}
+
+fn read_u32_be(reader: &mut impl Read) -> Result<u32, SwhidError> {
+    let mut bytes = [0u8; 4];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|e| SwhidError::InvalidGitObject(format!("truncated pack: {e}")))?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+/// Parse one object's type+size header, the first bytes of a packed object
+/// entry: a continuation-tagged varint whose first byte also carries the
+/// 3-bit type in its middle bits.
+fn read_object_header(reader: &mut impl Read) -> Result<(u8, u64), SwhidError> {
+    let mut byte = read_u8(reader)?;
+    let object_type = (byte >> 4) & 0x07;
+    let mut size: u64 = (byte & 0x0f) as u64;
+    let mut shift = 4;
+    while byte & 0x80 != 0 {
+        byte = read_u8(reader)?;
+        size |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+    }
+    Ok((object_type, size))
+}
+
+/// Parse an OFS_DELTA's base offset: a big-endian-ish varint where each
+/// continuation byte represents the base object's offset *backwards* from
+/// this delta's own offset in the pack.
+fn read_ofs_delta_offset(reader: &mut impl Read) -> Result<u64, SwhidError> {
+    let mut byte = read_u8(reader)?;
+    let mut offset: u64 = (byte & 0x7f) as u64;
+    while byte & 0x80 != 0 {
+        byte = read_u8(reader)?;
+        offset = ((offset + 1) << 7) | (byte & 0x7f) as u64;
+    }
+    Ok(offset)
+}
+
+fn read_object_type(code: u8) -> Result<ObjectType, SwhidError> {
+    match code {
+        OBJ_COMMIT => Ok(ObjectType::Revision),
+        OBJ_TREE => Ok(ObjectType::Directory),
+        OBJ_BLOB => Ok(ObjectType::Content),
+        OBJ_TAG => Ok(ObjectType::Release),
+        other => Err(SwhidError::InvalidGitObject(format!(
+            "unsupported packed object type code {other}"
+        ))),
+    }
+}
+
+/// Read a little-endian base-128 varint used inside delta instructions
+/// (distinct from [`read_object_header`]'s header varint).
+fn read_delta_varint(data: &[u8], pos: &mut usize) -> Result<u64, SwhidError> {
+    let mut result: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| SwhidError::InvalidGitObject("truncated delta".into()))?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+/// Apply a git delta (as produced for OFS_DELTA/REF_DELTA entries) to its
+/// base object's bytes, reproducing the derived object's full content.
+fn apply_delta(base: &[u8], delta: &[u8]) -> Result<Vec<u8>, SwhidError> {
+    let mut pos = 0;
+    let base_size = read_delta_varint(delta, &mut pos)?;
+    if base_size as usize != base.len() {
+        return Err(SwhidError::InvalidGitObject(format!(
+            "delta expects a {base_size}-byte base, got {}",
+            base.len()
+        )));
+    }
+    let result_size = read_delta_varint(delta, &mut pos)?;
+    if result_size > MAX_TRUSTED_OBJECT_SIZE {
+        return Err(SwhidError::InvalidGitObject(format!(
+            "delta declares an implausible result size of {result_size} bytes"
+        )));
+    }
+    let mut output = Vec::with_capacity(result_size as usize);
+
+    while pos < delta.len() {
+        let opcode = delta[pos];
+        pos += 1;
+        if opcode & 0x80 != 0 {
+            let mut offset: u32 = 0;
+            let mut size: u32 = 0;
+            for i in 0..4u32 {
+                if opcode & (1 << i) != 0 {
+                    let byte = *delta
+                        .get(pos)
+                        .ok_or_else(|| SwhidError::InvalidGitObject("truncated delta copy offset".into()))?;
+                    offset |= (byte as u32) << (8 * i);
+                    pos += 1;
+                }
+            }
+            for i in 0..3u32 {
+                if opcode & (1 << (4 + i)) != 0 {
+                    let byte = *delta
+                        .get(pos)
+                        .ok_or_else(|| SwhidError::InvalidGitObject("truncated delta copy size".into()))?;
+                    size |= (byte as u32) << (8 * i);
+                    pos += 1;
+                }
+            }
+            if size == 0 {
+                size = 0x10000;
+            }
+            let start = offset as usize;
+            let end = start
+                .checked_add(size as usize)
+                .ok_or_else(|| SwhidError::InvalidGitObject("delta copy range overflows".into()))?;
+            let slice = base
+                .get(start..end)
+                .ok_or_else(|| SwhidError::InvalidGitObject("delta copy reads past the base object".into()))?;
+            output.extend_from_slice(slice);
+        } else if opcode != 0 {
+            let len = opcode as usize;
+            let slice = delta
+                .get(pos..pos + len)
+                .ok_or_else(|| SwhidError::InvalidGitObject("truncated delta insert".into()))?;
+            output.extend_from_slice(slice);
+            pos += len;
+        } else {
+            return Err(SwhidError::InvalidGitObject(
+                "delta opcode 0 is reserved".into(),
+            ));
+        }
+    }
+
+    if output.len() as u64 != result_size {
+        return Err(SwhidError::InvalidGitObject(format!(
+            "delta produced {} bytes, expected {result_size}",
+            output.len()
+        )));
+    }
+    Ok(output)
+}
+
+fn parse_pack<R: Read + Seek>(reader: &mut R) -> Result<Vec<(ObjectType, Vec<u8>)>, SwhidError> {
+    let mut magic = [0u8; 4];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| SwhidError::InvalidGitObject(format!("truncated pack: {e}")))?;
+    if &magic != b"PACK" {
+        return Err(SwhidError::InvalidGitObject(
+            "not a packfile: missing PACK magic".into(),
+        ));
+    }
+    let _version = read_u32_be(reader)?;
+    let object_count = read_u32_be(reader)?;
+
+    let mut objects: BTreeMap<u64, RawObject> = BTreeMap::new();
+
+    for _ in 0..object_count {
+        let start_offset = reader
+            .stream_position()
+            .map_err(|e| SwhidError::InvalidGitObject(format!("failed to read pack position: {e}")))?;
+        let (type_code, inflated_size) = read_object_header(reader)?;
+
+        let raw = match type_code {
+            OBJ_OFS_DELTA => {
+                let backwards = read_ofs_delta_offset(reader)?;
+                let base_offset = start_offset.checked_sub(backwards).ok_or_else(|| {
+                    SwhidError::InvalidGitObject("OFS_DELTA offset points before the pack start".into())
+                })?;
+                let data = inflate_entry(reader, inflated_size)?;
+                RawObject::OfsDelta { base_offset, data }
+            }
+            OBJ_REF_DELTA => {
+                let mut base_sha1 = [0u8; 20];
+                reader
+                    .read_exact(&mut base_sha1)
+                    .map_err(|e| SwhidError::InvalidGitObject(format!("truncated pack: {e}")))?;
+                let data = inflate_entry(reader, inflated_size)?;
+                RawObject::RefDelta { base_sha1, data }
+            }
+            _ => {
+                let object_type = read_object_type(type_code)?;
+                let data = inflate_entry(reader, inflated_size)?;
+                RawObject::Base { object_type, data }
+            }
+        };
+        objects.insert(start_offset, raw);
+    }
+
+    let mut resolved: BTreeMap<u64, (ObjectType, Vec<u8>)> = BTreeMap::new();
+    let mut sha1_to_offset: HashMap<[u8; 20], u64> = HashMap::new();
+
+    for (&offset, raw) in &objects {
+        let value = match raw {
+            RawObject::Base { object_type, data } => (*object_type, data.clone()),
+            RawObject::OfsDelta { base_offset, data } => {
+                let (base_type, base_data) = resolved.get(base_offset).ok_or_else(|| {
+                    SwhidError::InvalidGitObject(
+                        "OFS_DELTA base is not an earlier object in this pack".into(),
+                    )
+                })?;
+                (*base_type, apply_delta(base_data, data)?)
+            }
+            RawObject::RefDelta { base_sha1, data } => {
+                let base_offset = sha1_to_offset.get(base_sha1).ok_or_else(|| {
+                    SwhidError::InvalidGitObject(
+                        "REF_DELTA base is not an earlier object in this pack".into(),
+                    )
+                })?;
+                let (base_type, base_data) = resolved.get(base_offset).ok_or_else(|| {
+                    SwhidError::InvalidGitObject(
+                        "REF_DELTA base is not an earlier object in this pack".into(),
+                    )
+                })?;
+                (*base_type, apply_delta(base_data, data)?)
+            }
+        };
+        let sha1 = hash_git_object(git_label(value.0), &value.1);
+        sha1_to_offset.insert(sha1, offset);
+        resolved.insert(offset, value);
+    }
+
+    Ok(resolved.into_values().collect())
+}
+
+fn inflate_entry<R: Read + Seek>(reader: &mut R, inflated_size: u64) -> Result<Vec<u8>, SwhidError> {
+    if inflated_size > MAX_TRUSTED_OBJECT_SIZE {
+        return Err(SwhidError::InvalidGitObject(format!(
+            "pack entry declares an implausible inflated size of {inflated_size} bytes"
+        )));
+    }
+    let zlib_start = reader
+        .stream_position()
+        .map_err(|e| SwhidError::InvalidGitObject(format!("failed to read pack position: {e}")))?;
+    let mut data = Vec::with_capacity(inflated_size as usize);
+    {
+        let mut decoder = ZlibDecoder::new(&mut *reader);
+        decoder
+            .read_to_end(&mut data)
+            .map_err(|e| SwhidError::InvalidGitObject(format!("failed to inflate pack entry: {e}")))?;
+        if data.len() as u64 != inflated_size {
+            return Err(SwhidError::InvalidGitObject(format!(
+                "pack entry inflated to {} bytes, header declared {inflated_size}",
+                data.len()
+            )));
+        }
+        let consumed = decoder.total_in();
+        reader
+            .seek(SeekFrom::Start(zlib_start + consumed))
+            .map_err(|e| SwhidError::InvalidGitObject(format!("failed to seek past pack entry: {e}")))?;
+    }
+    Ok(data)
+}
+
+enum PackObjectsIter {
+    Values(std::vec::IntoIter<(ObjectType, Vec<u8>)>),
+    Failed(Option<SwhidError>),
+}
+
+impl Iterator for PackObjectsIter {
+    type Item = Result<(ObjectType, Vec<u8>), SwhidError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            PackObjectsIter::Values(iter) => iter.next().map(Ok),
+            PackObjectsIter::Failed(error) => error.take().map(Err),
+        }
+    }
+}
+
+/// Decode every object in a git packfile, resolving OFS_DELTA and REF_DELTA
+/// entries against other objects within the same pack, and yield each as
+/// its [`ObjectType`] plus raw (undeltified) object bytes — ready to feed
+/// into [`crate::Content::from_data`], [`crate::Directory::from_raw_manifest`],
+/// etc. to compute a SWHID.
+///
+/// Objects are parsed eagerly (deltas can reference any earlier offset in
+/// the pack, so the whole pack must be read before anything can be
+/// yielded); a parse error surfaces as a single `Err` item.
+pub fn iter_pack_objects<R: Read + Seek>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<(ObjectType, Vec<u8>), SwhidError>> {
+    match parse_pack(&mut reader) {
+        Ok(objects) => PackObjectsIter::Values(objects.into_iter()),
+        Err(error) => PackObjectsIter::Failed(Some(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn write_object_header(out: &mut Vec<u8>, type_code: u8, size: usize) {
+        let mut size = size as u64;
+        let mut byte = ((type_code & 0x07) << 4) | (size as u8 & 0x0f);
+        size >>= 4;
+        if size > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        while size > 0 {
+            let mut next = (size & 0x7f) as u8;
+            size >>= 7;
+            if size > 0 {
+                next |= 0x80;
+            }
+            out.push(next);
+        }
+    }
+
+    fn build_pack(entries: Vec<(u8, Option<[u8; 20]>, Vec<u8>)>) -> Vec<u8> {
+        let mut pack = Vec::new();
+        pack.extend_from_slice(b"PACK");
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for (type_code, ref_base, payload) in entries {
+            write_object_header(&mut pack, type_code, payload.len());
+            if let Some(base) = ref_base {
+                pack.extend_from_slice(&base);
+            }
+            pack.extend_from_slice(&deflate(&payload));
+        }
+        pack
+    }
+
+    #[test]
+    fn decodes_a_plain_blob_with_no_deltas() {
+        let pack = build_pack(vec![(OBJ_BLOB, None, b"hello world".to_vec())]);
+        let objects: Vec<_> = iter_pack_objects(Cursor::new(pack))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(objects, vec![(ObjectType::Content, b"hello world".to_vec())]);
+    }
+
+    #[test]
+    fn resolves_a_ref_delta_against_an_earlier_blob() {
+        let base = b"hello world".to_vec();
+        // base_size varint, result_size varint, then one copy-all instruction.
+        let delta = vec![
+            base.len() as u8,
+            base.len() as u8,
+            0x80 | 0x10, // copy, size byte present
+            base.len() as u8,
+        ];
+        let base_sha1 = hash_git_object("blob", &base);
+
+        let pack = build_pack(vec![
+            (OBJ_BLOB, None, base),
+            (OBJ_REF_DELTA, Some(base_sha1), delta),
+        ]);
+        let objects: Vec<_> = iter_pack_objects(Cursor::new(pack))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[1], (ObjectType::Content, b"hello world".to_vec()));
+    }
+
+    #[test]
+    fn rejects_a_buffer_missing_the_pack_magic() {
+        let mut result = iter_pack_objects(Cursor::new(b"not-a-pack".to_vec()));
+        assert!(result.next().unwrap().is_err());
+    }
+
+    fn encode_delta_varint(mut n: u64, out: &mut Vec<u8>) {
+        loop {
+            let mut byte = (n & 0x7f) as u8;
+            n >>= 7;
+            if n > 0 {
+                byte |= 0x80;
+            }
+            out.push(byte);
+            if n == 0 {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn apply_delta_rejects_an_implausible_result_size_instead_of_aborting_on_allocation() {
+        let mut delta = Vec::new();
+        encode_delta_varint(0, &mut delta); // base_size: matches an empty base
+        encode_delta_varint(MAX_TRUSTED_OBJECT_SIZE + 1, &mut delta); // result_size: bogus
+        let err = apply_delta(b"", &delta).unwrap_err();
+        assert!(matches!(err, SwhidError::InvalidGitObject(_)));
+    }
+
+    #[test]
+    fn inflate_entry_rejects_an_implausible_inflated_size_instead_of_aborting_on_allocation() {
+        let mut reader = Cursor::new(deflate(b"hello"));
+        let err = inflate_entry(&mut reader, MAX_TRUSTED_OBJECT_SIZE + 1).unwrap_err();
+        assert!(matches!(err, SwhidError::InvalidGitObject(_)));
+    }
+}
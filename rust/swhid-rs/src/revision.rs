@@ -0,0 +1,1745 @@
+//! Git-shaped objects above the tree level: revisions (commits), releases
+//! (tags) and snapshots (refs listings).
+
+use crate::error::SwhidError;
+use crate::hash::{hash_git_object, GitSha1};
+use crate::swhid::{ObjectType, Swhid};
+use crate::verify::SelfConsistent;
+
+/// An author/committer identity, as it appears in a git object header.
+///
+/// [`Person::new`] composes `name`/`email` into the `Name <email>` form on
+/// demand, which is exact for well-formed identities. [`Person::from_raw`]
+/// goes the other way, for a fullname that's already in that form and whose
+/// exact bytes must reach the hash unchanged even if it's malformed (e.g.
+/// more than one `<...>`): it stores the raw bytes alongside a best-effort
+/// `name`/`email` split, and [`Person::fullname`] prefers the raw bytes when
+/// present.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Person {
+    pub name: Vec<u8>,
+    pub email: Vec<u8>,
+    raw_fullname: Option<Vec<u8>>,
+}
+
+impl Person {
+    pub fn new(name: impl Into<Vec<u8>>, email: impl Into<Vec<u8>>) -> Self {
+        Person {
+            name: name.into(),
+            email: email.into(),
+            raw_fullname: None,
+        }
+    }
+
+    /// Build a `Person` from an already-assembled `Name <email>` fullname,
+    /// preserving its exact bytes for [`Person::fullname`] regardless of how
+    /// `name`/`email` end up parsed out of it. `name`/`email` are filled in
+    /// on a best-effort basis (everything before the *last* `<...>` pair is
+    /// the name, trimmed of one trailing space; everything inside it is the
+    /// email), for display and [`Person::to_dict`] — but unlike
+    /// [`Person::new`], they never affect the hashed bytes.
+    pub fn from_raw(fullname: Vec<u8>) -> Self {
+        let (name, email) = split_fullname(&fullname);
+        Person {
+            name,
+            email,
+            raw_fullname: Some(fullname),
+        }
+    }
+
+    /// The `Name <email>` form git embeds in commit/tag headers: the raw
+    /// bytes a [`Person::from_raw`] was built from if any, otherwise `name`
+    /// and `email` composed together.
+    pub fn fullname(&self) -> Vec<u8> {
+        if let Some(raw) = &self.raw_fullname {
+            return raw.clone();
+        }
+        let mut bytes = Vec::with_capacity(self.name.len() + self.email.len() + 3);
+        bytes.extend_from_slice(&self.name);
+        bytes.extend_from_slice(b" <");
+        bytes.extend_from_slice(&self.email);
+        bytes.push(b'>');
+        bytes
+    }
+
+    /// Serialize to JSON, hex-encoding `name`/`email` since git identities
+    /// are arbitrary bytes with no guaranteed text encoding.
+    pub fn to_dict(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": hex::encode(&self.name),
+            "email": hex::encode(&self.email),
+        })
+    }
+
+    pub fn from_dict(value: &serde_json::Value) -> Result<Self, SwhidError> {
+        Ok(Person {
+            name: decode_hex_field(value, "name")?,
+            email: decode_hex_field(value, "email")?,
+            raw_fullname: None,
+        })
+    }
+}
+
+/// Best-effort `name`/`email` split for [`Person::from_raw`]: the email is
+/// the contents of the *last* `<...>` pair (so a name that itself contains
+/// `<...>`, however unusual, doesn't confuse the split), and the name is
+/// everything before it with at most one trailing space trimmed. A fullname
+/// with no `<...>` at all becomes an empty email and the whole input as the
+/// name.
+fn split_fullname(fullname: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let Some(close) = fullname.iter().rposition(|&b| b == b'>') else {
+        return (fullname.to_vec(), Vec::new());
+    };
+    let Some(open) = fullname[..close].iter().rposition(|&b| b == b'<') else {
+        return (fullname.to_vec(), Vec::new());
+    };
+    let email = fullname[open + 1..close].to_vec();
+    let mut name = fullname[..open].to_vec();
+    if name.last() == Some(&b' ') {
+        name.pop();
+    }
+    (name, email)
+}
+
+/// Read `value[field]` as a string and hex-decode it, for the byte fields
+/// [`Person::to_dict`]/[`GitTimestamp`]/friends hex-encode to survive JSON's
+/// lack of a raw-bytes type.
+fn decode_hex_field(value: &serde_json::Value, field: &str) -> Result<Vec<u8>, SwhidError> {
+    let hex_str = value
+        .get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| SwhidError::InvalidGitObject(format!("missing or non-string field: {field}")))?;
+    hex::decode(hex_str)
+        .map_err(|_| SwhidError::InvalidGitObject(format!("invalid hex in field {field}: {hex_str:?}")))
+}
+
+/// A git-style timestamp: seconds since the epoch plus a raw `+HHMM`/`-HHMM`
+/// offset string, kept as-is rather than parsed so unusual offsets
+/// round-trip exactly. `micros` is sub-second precision for non-git
+/// contexts (see [`GitTimestamp::with_micros`]) — git commit/tag headers
+/// have no fractional-second form, so it plays no part in [`GitTimestamp::to_bytes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitTimestamp {
+    pub seconds: i64,
+    pub offset: String,
+    pub micros: u32,
+}
+
+impl GitTimestamp {
+    pub fn new(seconds: i64, offset: impl Into<String>) -> Self {
+        GitTimestamp {
+            seconds,
+            offset: offset.into(),
+            micros: 0,
+        }
+    }
+
+    /// Attach sub-second precision, for display or interop with sources
+    /// that track it (e.g. a VCS import preserving original commit times).
+    /// Git itself has no fractional-second timestamp format, so this is
+    /// ignored by [`GitTimestamp::to_bytes`] and therefore by hashing —
+    /// see [`GitTimestamp::fractional_seconds_string`] for a representation
+    /// that does include it.
+    pub fn with_micros(mut self, micros: u32) -> Self {
+        self.micros = micros;
+        self
+    }
+
+    /// The exact bytes git expects in a commit/tag header: integer seconds
+    /// only. Any [`GitTimestamp::micros`] are truncated, since
+    /// `<seconds>.<micros> <offset>` is not a git timestamp and would hash
+    /// to the wrong SWHID.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format!("{} {}", self.seconds, self.offset).into_bytes()
+    }
+
+    /// `seconds.micros offset`, for non-git contexts that want
+    /// sub-second precision (e.g. logging or a richer timestamp display).
+    /// Never used when hashing — see [`GitTimestamp::to_bytes`].
+    pub fn fractional_seconds_string(&self) -> String {
+        format!("{}.{:06} {}", self.seconds, self.micros, self.offset)
+    }
+
+    /// Parse the raw `+HHMM`/`-HHMM` offset string into minutes east of UTC
+    /// (e.g. `"+0530"` is `330`, `"-0700"` is `-420`).
+    ///
+    /// `self.offset` comes from wherever the `GitTimestamp` was built (a
+    /// parsed git object, a JSON blob, ...) and isn't guaranteed to be
+    /// well-formed, so this never indexes into it by byte position — a
+    /// multibyte character at, say, the sign position would make a raw
+    /// `self.offset[0..1]` slice panic instead of just failing to parse.
+    pub fn offset_minutes(&self) -> Result<i32, SwhidError> {
+        let invalid = || {
+            SwhidError::InvalidGitObject(format!("invalid timezone offset: {:?}", self.offset))
+        };
+        if !self.offset.is_ascii() || self.offset.len() != 5 {
+            return Err(invalid());
+        }
+        let sign = match self.offset.as_bytes()[0] {
+            b'+' => 1,
+            b'-' => -1,
+            _ => return Err(invalid()),
+        };
+        let hours: i32 = self.offset[1..3].parse().map_err(|_| invalid())?;
+        let minutes: i32 = self.offset[3..5].parse().map_err(|_| invalid())?;
+        Ok(sign * (hours * 60 + minutes))
+    }
+
+    /// Serialize to JSON as `{"seconds", "offset", "micros"}`.
+    pub fn to_dict(&self) -> serde_json::Value {
+        serde_json::json!({
+            "seconds": self.seconds,
+            "offset": self.offset,
+            "micros": self.micros,
+        })
+    }
+
+    pub fn from_dict(value: &serde_json::Value) -> Result<Self, SwhidError> {
+        let seconds = value
+            .get("seconds")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-integer field: seconds".into()))?;
+        let offset = value
+            .get("offset")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-string field: offset".into()))?
+            .to_string();
+        let micros = value
+            .get("micros")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32;
+        Ok(GitTimestamp { seconds, offset, micros })
+    }
+
+    /// Build a `chrono::DateTime<FixedOffset>` in this timestamp's original
+    /// authored timezone, rather than converting to UTC. Useful for
+    /// displaying commit times the way the author saw them.
+    pub fn to_datetime_with_offset(&self) -> Result<chrono::DateTime<chrono::FixedOffset>, SwhidError> {
+        use chrono::TimeZone;
+
+        let offset_seconds = self.offset_minutes()? * 60;
+        let fixed_offset = chrono::FixedOffset::east_opt(offset_seconds).ok_or_else(|| {
+            SwhidError::InvalidGitObject(format!(
+                "timezone offset out of range: {:?}",
+                self.offset
+            ))
+        })?;
+        fixed_offset
+            .timestamp_opt(self.seconds, 0)
+            .single()
+            .ok_or_else(|| {
+                SwhidError::InvalidGitObject(format!(
+                    "timestamp out of range: {}",
+                    self.seconds
+                ))
+            })
+    }
+
+    /// Parse an RFC 3339 / ISO 8601 string (e.g. `"2024-01-02T03:04:05+05:30"`)
+    /// into a `GitTimestamp`, for building revisions/releases directly from
+    /// JSON date fields without a manual `chrono` round trip. The timezone
+    /// offset carried in `s` is preserved verbatim as `±HHMM`, and
+    /// fractional seconds (if any) populate [`GitTimestamp::micros`].
+    ///
+    /// Re-validates the parsed seconds the same way
+    /// [`GitTimestamp::to_datetime_with_offset`] does, so a `GitTimestamp`
+    /// built this way is guaranteed to convert back without error.
+    pub fn from_rfc3339(s: &str) -> Result<Self, SwhidError> {
+        let parsed = chrono::DateTime::parse_from_rfc3339(s)
+            .map_err(|e| SwhidError::InvalidGitObject(format!("invalid RFC 3339 timestamp {s:?}: {e}")))?;
+
+        let offset_minutes = parsed.offset().local_minus_utc() / 60;
+        let sign = if offset_minutes < 0 { '-' } else { '+' };
+        let offset = format!(
+            "{sign}{:02}{:02}",
+            offset_minutes.abs() / 60,
+            offset_minutes.abs() % 60
+        );
+
+        let timestamp = GitTimestamp {
+            seconds: parsed.timestamp(),
+            offset,
+            micros: parsed.timestamp_subsec_micros(),
+        };
+        // Catches the offsets `chrono` itself would refuse to round-trip
+        // (e.g. whole-day offsets out of `FixedOffset`'s range).
+        timestamp.to_datetime_with_offset()?;
+        Ok(timestamp)
+    }
+}
+
+/// A revision (git commit) object.
+///
+/// Software Heritage computes a revision's SWHID from this same git-style
+/// `commit` manifest regardless of the origin VCS the revision was loaded
+/// from (git, Mercurial, a tarball, a Debian source package, ...) — this
+/// crate has no `RevisionType`/origin-VCS enum, and hashing is uniform.
+/// Any origin-specific metadata (e.g. a Mercurial changeset's extra
+/// fields, or a tarball/dsc revision's synthetic identity) is carried as
+/// ordinary entries in [`Revision::extra_headers`], the same mechanism
+/// used for `gpgsig`/`mergetag`, rather than through a dedicated type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub directory: GitSha1,
+    pub parents: Vec<GitSha1>,
+    pub author: Person,
+    pub author_date: GitTimestamp,
+    pub committer: Person,
+    pub committer_date: GitTimestamp,
+    pub message: Vec<u8>,
+    /// Headers beyond the fixed `tree`/`parent`/`author`/`committer` set
+    /// (e.g. `gpgsig`, `mergetag`), in the order they should be emitted.
+    /// Unlike the fixed headers, git preserves whatever order the original
+    /// commit author wrote these in, so they are kept in insertion order
+    /// rather than being sorted.
+    pub extra_headers: Vec<(Vec<u8>, Vec<u8>)>,
+    /// The exact bytes this revision was parsed from, for commits that
+    /// don't round-trip through [`Revision::to_git_object`] (e.g. unusual
+    /// header ordering or spacing). When set, [`Revision::compute_hash`]
+    /// hashes these bytes directly instead of regenerating them.
+    pub raw_manifest: Option<Vec<u8>>,
+}
+
+impl Revision {
+    /// Start building a revision with [`RevisionBuilder`], which enforces
+    /// the canonical git header order (`tree`, `parent`s, `author`,
+    /// `committer`, then extras) and validates required fields are set,
+    /// so callers can't accidentally misorder headers and produce an
+    /// invalid commit SWHID.
+    pub fn builder() -> RevisionBuilder {
+        RevisionBuilder::default()
+    }
+
+    /// Regenerate the canonical `commit` object body from this revision's
+    /// fields.
+    pub fn to_git_object(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(b"tree ");
+        body.extend_from_slice(self.directory.to_hex().as_bytes());
+        body.push(b'\n');
+        for parent in &self.parents {
+            body.extend_from_slice(b"parent ");
+            body.extend_from_slice(parent.to_hex().as_bytes());
+            body.push(b'\n');
+        }
+        body.extend_from_slice(b"author ");
+        body.extend_from_slice(&self.author.fullname());
+        body.push(b' ');
+        body.extend_from_slice(&self.author_date.to_bytes());
+        body.push(b'\n');
+        body.extend_from_slice(b"committer ");
+        body.extend_from_slice(&self.committer.fullname());
+        body.push(b' ');
+        body.extend_from_slice(&self.committer_date.to_bytes());
+        body.push(b'\n');
+        for (key, value) in &self.extra_headers {
+            body.extend_from_slice(key);
+            body.push(b' ');
+            body.extend_from_slice(&fold_header_value(value));
+            body.push(b'\n');
+        }
+        body.push(b'\n');
+        body.extend_from_slice(&self.message);
+        body
+    }
+
+    /// Hash `raw_manifest` directly when present (for revisions whose
+    /// original encoding doesn't round-trip), otherwise regenerate the
+    /// canonical object from fields.
+    pub fn compute_hash(&self) -> [u8; 20] {
+        match &self.raw_manifest {
+            Some(raw) => hash_git_object("commit", raw),
+            None => hash_git_object("commit", &self.to_git_object()),
+        }
+    }
+
+    pub fn swhid(&self) -> Swhid {
+        Swhid::new(ObjectType::Revision, self.compute_hash())
+    }
+
+    /// Whether this revision's `raw_manifest` (if any) still reproduces
+    /// from its fields, i.e. [`SelfConsistent::verify_self_consistent`]
+    /// succeeds. Catches corruption or a serializer mismatch in a revision
+    /// deserialized from storage.
+    pub fn verify(&self) -> bool {
+        self.verify_self_consistent().is_ok()
+    }
+
+    /// Serialize to a JSON object, for interchange with storage or the
+    /// Python swh-model tooling: hashes as hex, persons/dates as their own
+    /// nested dicts, and `message`/extra header values hex-encoded since
+    /// they're arbitrary bytes. Doesn't carry `raw_manifest` — a revision
+    /// round-tripped through [`Revision::from_dict`] always hashes via the
+    /// canonical [`Revision::to_git_object`] encoding.
+    pub fn to_dict(&self) -> serde_json::Value {
+        serde_json::json!({
+            "directory": self.directory.to_hex(),
+            "parents": self.parents.iter().map(GitSha1::to_hex).collect::<Vec<_>>(),
+            "author": self.author.to_dict(),
+            "author_date": self.author_date.to_dict(),
+            "committer": self.committer.to_dict(),
+            "committer_date": self.committer_date.to_dict(),
+            "message": hex::encode(&self.message),
+            "extra_headers": self.extra_headers.iter()
+                .map(|(key, value)| serde_json::json!([hex::encode(key), hex::encode(value)]))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    /// Parse a JSON object produced by [`Revision::to_dict`].
+    pub fn from_dict(value: &serde_json::Value) -> Result<Self, SwhidError> {
+        let directory = decode_hex_hash(value, "directory")?.into();
+        let parents = value
+            .get("parents")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-array field: parents".into()))?
+            .iter()
+            .map(|parent| {
+                let hex_str = parent.as_str().ok_or_else(|| {
+                    SwhidError::InvalidGitObject("parent entry is not a string".into())
+                })?;
+                decode_hex_hash_str(hex_str).map(GitSha1::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let author = Person::from_dict(value.get("author").ok_or_else(|| {
+            SwhidError::InvalidGitObject("missing field: author".into())
+        })?)?;
+        let author_date = GitTimestamp::from_dict(value.get("author_date").ok_or_else(|| {
+            SwhidError::InvalidGitObject("missing field: author_date".into())
+        })?)?;
+        let committer = Person::from_dict(value.get("committer").ok_or_else(|| {
+            SwhidError::InvalidGitObject("missing field: committer".into())
+        })?)?;
+        let committer_date = GitTimestamp::from_dict(value.get("committer_date").ok_or_else(|| {
+            SwhidError::InvalidGitObject("missing field: committer_date".into())
+        })?)?;
+        let message = decode_hex_field(value, "message")?;
+        let extra_headers = value
+            .get("extra_headers")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-array field: extra_headers".into()))?
+            .iter()
+            .map(|entry| {
+                let pair = entry.as_array().filter(|a| a.len() == 2).ok_or_else(|| {
+                    SwhidError::InvalidGitObject("extra_headers entry is not a 2-element array".into())
+                })?;
+                let key = pair[0].as_str().ok_or_else(|| {
+                    SwhidError::InvalidGitObject("extra header key is not a string".into())
+                })?;
+                let value = pair[1].as_str().ok_or_else(|| {
+                    SwhidError::InvalidGitObject("extra header value is not a string".into())
+                })?;
+                Ok((decode_hex_field_str(key)?, decode_hex_field_str(value)?))
+            })
+            .collect::<Result<Vec<_>, SwhidError>>()?;
+        Ok(Revision {
+            directory,
+            parents,
+            author,
+            author_date,
+            committer,
+            committer_date,
+            message,
+            extra_headers,
+            raw_manifest: None,
+        })
+    }
+}
+
+/// Hex-decode `value[field]` to a `[u8; 20]` git-sha1.
+fn decode_hex_hash(value: &serde_json::Value, field: &str) -> Result<[u8; 20], SwhidError> {
+    let hex_str = value
+        .get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| SwhidError::InvalidGitObject(format!("missing or non-string field: {field}")))?;
+    decode_hex_hash_str(hex_str)
+}
+
+fn decode_hex_hash_str(hex_str: &str) -> Result<[u8; 20], SwhidError> {
+    let bytes = hex::decode(hex_str)
+        .map_err(|_| SwhidError::InvalidGitObject(format!("invalid hex hash: {hex_str:?}")))?;
+    bytes
+        .try_into()
+        .map_err(|_| SwhidError::InvalidGitObject(format!("hash is not 20 bytes: {hex_str:?}")))
+}
+
+fn decode_hex_field_str(hex_str: &str) -> Result<Vec<u8>, SwhidError> {
+    hex::decode(hex_str)
+        .map_err(|_| SwhidError::InvalidGitObject(format!("invalid hex: {hex_str:?}")))
+}
+
+/// git folds a header value's embedded newlines by indenting continuation
+/// lines with a single space, so a multi-line value (e.g. a `gpgsig`
+/// signature) still parses back out as one logical header.
+fn fold_header_value(value: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.len());
+    let mut first = true;
+    for line in value.split(|&b| b == b'\n') {
+        if !first {
+            out.extend_from_slice(b"\n ");
+        }
+        out.extend_from_slice(line);
+        first = false;
+    }
+    out
+}
+
+/// Builds a [`Revision`] while enforcing the canonical git commit header
+/// order (`tree`, `parent`s, `author`, `committer`, then extras) and
+/// validating that the required fields were actually set. Extra headers
+/// are kept in the order they're added via [`RevisionBuilder::extra_header`].
+#[derive(Debug, Clone, Default)]
+pub struct RevisionBuilder {
+    directory: Option<GitSha1>,
+    parents: Vec<GitSha1>,
+    author: Option<(Person, GitTimestamp)>,
+    committer: Option<(Person, GitTimestamp)>,
+    extra_headers: Vec<(Vec<u8>, Vec<u8>)>,
+    message: Vec<u8>,
+}
+
+impl RevisionBuilder {
+    pub fn directory(mut self, directory: impl Into<GitSha1>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    pub fn parent(mut self, parent: impl Into<GitSha1>) -> Self {
+        self.parents.push(parent.into());
+        self
+    }
+
+    pub fn parents(mut self, parents: impl IntoIterator<Item = impl Into<GitSha1>>) -> Self {
+        self.parents.extend(parents.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn author(mut self, author: Person, date: GitTimestamp) -> Self {
+        self.author = Some((author, date));
+        self
+    }
+
+    pub fn committer(mut self, committer: Person, date: GitTimestamp) -> Self {
+        self.committer = Some((committer, date));
+        self
+    }
+
+    /// Append an extra header (e.g. `gpgsig`, `mergetag`). Headers are kept
+    /// in the order they're added here, matching git's own preservation of
+    /// author-specified order.
+    pub fn extra_header(mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) -> Self {
+        self.extra_headers.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn message(mut self, message: impl Into<Vec<u8>>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Build the [`Revision`], failing if `directory`, `author` or
+    /// `committer` were never set.
+    pub fn build(self) -> Result<Revision, SwhidError> {
+        let directory = self
+            .directory
+            .ok_or_else(|| SwhidError::InvalidGitObject("revision is missing a tree".into()))?;
+        let (author, author_date) = self
+            .author
+            .ok_or_else(|| SwhidError::InvalidGitObject("revision is missing an author".into()))?;
+        let (committer, committer_date) = self.committer.ok_or_else(|| {
+            SwhidError::InvalidGitObject("revision is missing a committer".into())
+        })?;
+        Ok(Revision {
+            directory,
+            parents: self.parents,
+            author,
+            author_date,
+            committer,
+            committer_date,
+            message: self.message,
+            extra_headers: self.extra_headers,
+            raw_manifest: None,
+        })
+    }
+}
+
+/// A release (git annotated tag) object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub target: GitSha1,
+    pub target_type: ObjectType,
+    pub name: Vec<u8>,
+    pub author: Option<Person>,
+    pub date: Option<GitTimestamp>,
+    pub message: Vec<u8>,
+    pub raw_manifest: Option<Vec<u8>>,
+}
+
+impl Release {
+    pub fn to_git_object(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(b"object ");
+        body.extend_from_slice(self.target.to_hex().as_bytes());
+        body.push(b'\n');
+        body.extend_from_slice(b"type ");
+        body.extend_from_slice(self.target_type.as_str().as_bytes());
+        body.push(b'\n');
+        body.extend_from_slice(b"tag ");
+        body.extend_from_slice(&self.name);
+        body.push(b'\n');
+        if let (Some(author), Some(date)) = (&self.author, &self.date) {
+            body.extend_from_slice(b"tagger ");
+            body.extend_from_slice(&author.fullname());
+            body.push(b' ');
+            body.extend_from_slice(&date.to_bytes());
+            body.push(b'\n');
+        }
+        body.push(b'\n');
+        body.extend_from_slice(&self.message);
+        body
+    }
+
+    pub fn compute_hash(&self) -> [u8; 20] {
+        match &self.raw_manifest {
+            Some(raw) => hash_git_object("tag", raw),
+            None => hash_git_object("tag", &self.to_git_object()),
+        }
+    }
+
+    pub fn swhid(&self) -> Swhid {
+        Swhid::new(ObjectType::Release, self.compute_hash())
+    }
+
+    /// Whether this release's `raw_manifest` (if any) still reproduces from
+    /// its fields. See [`Revision::verify`].
+    pub fn verify(&self) -> bool {
+        self.verify_self_consistent().is_ok()
+    }
+
+    /// Serialize to JSON. See [`Revision::to_dict`] for the byte-field
+    /// hex-encoding convention.
+    pub fn to_dict(&self) -> serde_json::Value {
+        serde_json::json!({
+            "target": self.target.to_hex(),
+            "target_type": self.target_type.as_str(),
+            "name": hex::encode(&self.name),
+            "author": self.author.as_ref().map(Person::to_dict),
+            "date": self.date.as_ref().map(GitTimestamp::to_dict),
+            "message": hex::encode(&self.message),
+        })
+    }
+
+    /// Parse a JSON object produced by [`Release::to_dict`].
+    pub fn from_dict(value: &serde_json::Value) -> Result<Self, SwhidError> {
+        let target = decode_hex_hash(value, "target")?.into();
+        let target_type_str = value
+            .get("target_type")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-string field: target_type".into()))?;
+        let target_type = ObjectType::from_str_code(target_type_str)?;
+        let name = decode_hex_field(value, "name")?;
+        let author = match value.get("author") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(author) => Some(Person::from_dict(author)?),
+        };
+        let date = match value.get("date") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(date) => Some(GitTimestamp::from_dict(date)?),
+        };
+        let message = decode_hex_field(value, "message")?;
+        Ok(Release {
+            target,
+            target_type,
+            name,
+            author,
+            date,
+            message,
+            raw_manifest: None,
+        })
+    }
+}
+
+/// A single branch in a snapshot's refs listing: either a normal pointer at
+/// some other object, or an alias whose target is another branch's name
+/// (e.g. `HEAD` pointing at `refs/heads/main`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotBranch {
+    Object {
+        target: [u8; 20],
+        target_type: ObjectType,
+    },
+    Alias {
+        target: Vec<u8>,
+    },
+}
+
+impl SnapshotBranch {
+    fn manifest_type(&self) -> &'static str {
+        match self {
+            SnapshotBranch::Object { target_type, .. } => target_type.as_str(),
+            SnapshotBranch::Alias { .. } => "alias",
+        }
+    }
+
+    fn manifest_target(&self) -> &[u8] {
+        match self {
+            SnapshotBranch::Object { target, .. } => target,
+            SnapshotBranch::Alias { target } => target,
+        }
+    }
+
+    /// Serialize to JSON, as `{"target_type": "object"/"alias", ...}`.
+    pub fn to_dict(&self) -> serde_json::Value {
+        match self {
+            SnapshotBranch::Object { target, target_type } => serde_json::json!({
+                "target_type": "object",
+                "target": hex::encode(target),
+                "object_type": target_type.as_str(),
+            }),
+            SnapshotBranch::Alias { target } => serde_json::json!({
+                "target_type": "alias",
+                "target": hex::encode(target),
+            }),
+        }
+    }
+
+    /// Parse a JSON object produced by [`SnapshotBranch::to_dict`].
+    pub fn from_dict(value: &serde_json::Value) -> Result<Self, SwhidError> {
+        let target_type = value
+            .get("target_type")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-string field: target_type".into()))?;
+        match target_type {
+            "alias" => Ok(SnapshotBranch::Alias {
+                target: decode_hex_field(value, "target")?,
+            }),
+            "object" => {
+                let object_type_str = value
+                    .get("object_type")
+                    .and_then(|v| v.as_str())
+                    .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-string field: object_type".into()))?;
+                Ok(SnapshotBranch::Object {
+                    target: decode_hex_hash(value, "target")?,
+                    target_type: ObjectType::from_str_code(object_type_str)?,
+                })
+            }
+            other => Err(SwhidError::InvalidGitObject(format!(
+                "unknown branch target_type: {other:?}"
+            ))),
+        }
+    }
+}
+
+/// A snapshot of a repository's branches at a point in time. Branches with
+/// no target (dangling refs) are stored as `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    pub branches: std::collections::BTreeMap<Vec<u8>, Option<SnapshotBranch>>,
+    pub raw_manifest: Option<Vec<u8>>,
+}
+
+impl Snapshot {
+    /// The snapshot with zero branches — a useful sentinel for a repository
+    /// that has no refs yet. Its SWHID is the well-known
+    /// `swh:1:snp:1a8893e6a86f444e8be8e7bda6cb34fb1735a00e`.
+    pub fn empty() -> Self {
+        Snapshot::default()
+    }
+
+    pub fn to_manifest(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (name, branch) in &self.branches {
+            match branch {
+                Some(branch) => {
+                    body.extend_from_slice(branch.manifest_type().as_bytes());
+                    body.push(b' ');
+                }
+                None => body.extend_from_slice(b"dangling "),
+            }
+            body.extend_from_slice(name);
+            body.push(0);
+            if let Some(branch) = branch {
+                body.extend_from_slice(branch.manifest_target());
+            }
+        }
+        body
+    }
+
+    pub fn compute_hash(&self) -> [u8; 20] {
+        match &self.raw_manifest {
+            Some(raw) => hash_git_object("snapshot", raw),
+            None => hash_git_object("snapshot", &self.to_manifest()),
+        }
+    }
+
+    pub fn swhid(&self) -> Swhid {
+        Swhid::new(ObjectType::Snapshot, self.compute_hash())
+    }
+
+    /// Check that every [`SnapshotBranch::Alias`] target names a branch that
+    /// is actually present in this snapshot. This is an opt-in integrity
+    /// check, separate from hashing — a snapshot with a dangling alias still
+    /// hashes fine, it's just semantically broken. Non-alias targets are
+    /// always exactly 20 bytes by construction ([`SnapshotBranch::Object`]
+    /// stores a `[u8; 20]`), so there's nothing to check there.
+    pub fn validate(&self) -> Result<(), SwhidError> {
+        for (name, branch) in &self.branches {
+            let Some(SnapshotBranch::Alias { target }) = branch else {
+                continue;
+            };
+            if !self.branches.contains_key(target) {
+                return Err(SwhidError::InvalidGitObject(format!(
+                    "branch {:?} is an alias for {:?}, which is not a branch in this snapshot",
+                    String::from_utf8_lossy(name),
+                    String::from_utf8_lossy(target),
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether this snapshot's `raw_manifest` (if any) still reproduces
+    /// from its branches. See [`Revision::verify`].
+    pub fn verify(&self) -> bool {
+        self.verify_self_consistent().is_ok()
+    }
+
+    /// Peel every alias branch to its ultimate non-alias target, for
+    /// display or export — this never affects hashing, which always uses
+    /// the immediate target per [`SnapshotBranch::manifest_target`]. Returns
+    /// a map from branch name to the resolved `Option<SnapshotBranch>`
+    /// (`None` for a dangling branch — whether it's present in this
+    /// snapshot with no target, or an alias chain leads to a name this
+    /// snapshot doesn't have at all — possibly reached through one or more
+    /// aliases). Use [`Snapshot::validate`] first if an alias pointing at a
+    /// name genuinely absent from this snapshot should be an error instead
+    /// of folded into `None` here; this only errors if alias targets form
+    /// a loop.
+    pub fn resolved(
+        &self,
+    ) -> Result<std::collections::BTreeMap<Vec<u8>, Option<SnapshotBranch>>, SwhidError> {
+        let mut resolved = std::collections::BTreeMap::new();
+        for name in self.branches.keys() {
+            let target = self.resolve_branch(name, &mut Vec::new())?;
+            resolved.insert(name.clone(), target);
+        }
+        Ok(resolved)
+    }
+
+    /// Follow `name` through as many [`SnapshotBranch::Alias`] hops as it
+    /// takes to reach a non-alias branch (or a missing/dangling one),
+    /// tracking the chain of names already visited in `seen` to detect a
+    /// loop.
+    fn resolve_branch(
+        &self,
+        name: &[u8],
+        seen: &mut Vec<Vec<u8>>,
+    ) -> Result<Option<SnapshotBranch>, SwhidError> {
+        if seen.iter().any(|visited| visited == name) {
+            return Err(SwhidError::InvalidGitObject(format!(
+                "alias loop while resolving branch {:?}",
+                String::from_utf8_lossy(name)
+            )));
+        }
+        seen.push(name.to_vec());
+
+        match self.branches.get(name) {
+            None => Ok(None),
+            Some(None) => Ok(None),
+            Some(Some(SnapshotBranch::Alias { target })) => self.resolve_branch(target, seen),
+            Some(Some(branch)) => Ok(Some(branch.clone())),
+        }
+    }
+
+    /// Serialize to JSON: branches as an object keyed by the hex-encoded
+    /// branch name (branch names are arbitrary bytes, so they can't be used
+    /// as JSON object keys directly), a dangling branch mapping to `null`.
+    pub fn to_dict(&self) -> serde_json::Value {
+        let branches: serde_json::Map<String, serde_json::Value> = self
+            .branches
+            .iter()
+            .map(|(name, branch)| {
+                let value = match branch {
+                    Some(branch) => branch.to_dict(),
+                    None => serde_json::Value::Null,
+                };
+                (hex::encode(name), value)
+            })
+            .collect();
+        serde_json::json!({ "branches": branches })
+    }
+
+    /// Parse a JSON object produced by [`Snapshot::to_dict`].
+    pub fn from_dict(value: &serde_json::Value) -> Result<Self, SwhidError> {
+        let branches_obj = value
+            .get("branches")
+            .and_then(|v| v.as_object())
+            .ok_or_else(|| SwhidError::InvalidGitObject("missing or non-object field: branches".into()))?;
+        let mut branches = std::collections::BTreeMap::new();
+        for (name_hex, branch_value) in branches_obj {
+            let name = decode_hex_field_str(name_hex)?;
+            let branch = match branch_value {
+                serde_json::Value::Null => None,
+                other => Some(SnapshotBranch::from_dict(other)?),
+            };
+            branches.insert(name, branch);
+        }
+        Ok(Snapshot {
+            branches,
+            raw_manifest: None,
+        })
+    }
+
+    /// Build a snapshot from a `git show-ref`-style refs listing: pairs of
+    /// (ref name, target sha1), as they come straight out of the packed-refs
+    /// file or `git for-each-ref`. The target object type is guessed from
+    /// the ref name prefix via [`infer_ref_target_type`], since the sha1
+    /// alone doesn't say whether it names a commit, a tag object, etc.
+    ///
+    /// `git show-ref` resolves symbolic refs (like `HEAD`) to the sha1 they
+    /// point at, so it can't produce [`SnapshotBranch::Alias`] branches on
+    /// its own; add those separately with [`Snapshot::with_alias`] using
+    /// the output of `git symbolic-ref HEAD` or similar.
+    pub fn from_refs(refs: impl IntoIterator<Item = (Vec<u8>, [u8; 20])>) -> Snapshot {
+        let branches = refs
+            .into_iter()
+            .map(|(name, target)| {
+                let target_type = infer_ref_target_type(&name);
+                (name, Some(SnapshotBranch::Object { target, target_type }))
+            })
+            .collect();
+        Snapshot {
+            branches,
+            raw_manifest: None,
+        }
+    }
+
+    /// Add a symbolic ref (e.g. `HEAD` pointing at `refs/heads/main`) as an
+    /// alias branch, returning `self` for chaining onto [`Snapshot::from_refs`].
+    pub fn with_alias(mut self, name: impl Into<Vec<u8>>, target: impl Into<Vec<u8>>) -> Self {
+        self.branches.insert(
+            name.into(),
+            Some(SnapshotBranch::Alias {
+                target: target.into(),
+            }),
+        );
+        self
+    }
+}
+
+/// Guess the git object type a ref points at from its name alone, the way
+/// [`Snapshot::from_refs`] does when it only has a name and a sha1 to go on:
+///
+/// - `refs/tags/...` is usually an annotated tag, i.e. a release object;
+///   lightweight tags point directly at a revision instead, but that can't
+///   be told apart from the name, so this is a best-effort guess the caller
+///   should correct (e.g. by peeling the tag) when it matters.
+/// - `refs/heads/...` and anything else is treated as a revision, the
+///   common case for branches and other refs.
+pub fn infer_ref_target_type(name: &[u8]) -> ObjectType {
+    if name.starts_with(b"refs/tags/") {
+        ObjectType::Release
+    } else {
+        ObjectType::Revision
+    }
+}
+
+impl SelfConsistent for Revision {
+    fn verify_self_consistent(&self) -> Result<(), SwhidError> {
+        match &self.raw_manifest {
+            None => Ok(()),
+            Some(raw) if *raw == self.to_git_object() => Ok(()),
+            Some(_) => Err(SwhidError::InconsistentObject),
+        }
+    }
+}
+
+impl SelfConsistent for Release {
+    fn verify_self_consistent(&self) -> Result<(), SwhidError> {
+        match &self.raw_manifest {
+            None => Ok(()),
+            Some(raw) if *raw == self.to_git_object() => Ok(()),
+            Some(_) => Err(SwhidError::InconsistentObject),
+        }
+    }
+}
+
+impl SelfConsistent for Snapshot {
+    fn verify_self_consistent(&self) -> Result<(), SwhidError> {
+        match &self.raw_manifest {
+            None => Ok(()),
+            Some(raw) if *raw == self.to_manifest() => Ok(()),
+            Some(_) => Err(SwhidError::InconsistentObject),
+        }
+    }
+}
+
+impl crate::git_manifest::GitManifest for Revision {
+    fn git_type(&self) -> &'static str {
+        "commit"
+    }
+
+    fn manifest(&self) -> Vec<u8> {
+        match &self.raw_manifest {
+            Some(raw) => raw.clone(),
+            None => self.to_git_object(),
+        }
+    }
+
+    fn swhid(&self) -> Swhid {
+        Revision::swhid(self)
+    }
+}
+
+impl crate::git_manifest::GitManifest for Release {
+    fn git_type(&self) -> &'static str {
+        "tag"
+    }
+
+    fn manifest(&self) -> Vec<u8> {
+        match &self.raw_manifest {
+            Some(raw) => raw.clone(),
+            None => self.to_git_object(),
+        }
+    }
+
+    fn swhid(&self) -> Swhid {
+        Release::swhid(self)
+    }
+}
+
+impl crate::git_manifest::GitManifest for Snapshot {
+    fn git_type(&self) -> &'static str {
+        "snapshot"
+    }
+
+    fn manifest(&self) -> Vec<u8> {
+        match &self.raw_manifest {
+            Some(raw) => raw.clone(),
+            None => self.to_manifest(),
+        }
+    }
+
+    fn swhid(&self) -> Swhid {
+        Snapshot::swhid(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person() -> Person {
+        Person::new("Jane Dev", "jane@example.com")
+    }
+
+    fn timestamp() -> GitTimestamp {
+        GitTimestamp::new(1_700_000_000, "+0000")
+    }
+
+    #[test]
+    fn revision_with_a_microsecond_timestamp_hashes_using_integer_seconds() {
+        let whole_seconds = timestamp();
+        let with_micros = whole_seconds.clone().with_micros(500_000);
+        assert_eq!(whole_seconds.to_bytes(), with_micros.to_bytes());
+        assert_eq!(
+            with_micros.fractional_seconds_string(),
+            "1700000000.500000 +0000"
+        );
+
+        let make_revision = |author_date: GitTimestamp| Revision {
+            directory: [1u8; 20].into(),
+            parents: vec![],
+            author: person(),
+            author_date,
+            committer: person(),
+            committer_date: timestamp(),
+            message: b"msg".to_vec(),
+            extra_headers: vec![],
+            raw_manifest: None,
+        };
+        assert_eq!(
+            make_revision(whole_seconds).compute_hash(),
+            make_revision(with_micros).compute_hash()
+        );
+    }
+
+    #[test]
+    fn revision_raw_manifest_overrides_regenerated_bytes() {
+        let revision = Revision {
+            directory: [1u8; 20].into(),
+            parents: vec![],
+            author: person(),
+            author_date: timestamp(),
+            committer: person(),
+            committer_date: timestamp(),
+            message: b"msg".to_vec(),
+            extra_headers: vec![],
+            raw_manifest: None,
+        };
+        let from_fields = revision.compute_hash();
+        assert_eq!(hash_git_object("commit", &revision.to_git_object()), from_fields);
+
+        let quirky_raw = b"tree 0101010101010101010101010101010101010101\n\nquirky\n".to_vec();
+        let with_raw = Revision {
+            raw_manifest: Some(quirky_raw.clone()),
+            ..revision
+        };
+        assert_eq!(with_raw.compute_hash(), hash_git_object("commit", &quirky_raw));
+        assert_ne!(with_raw.compute_hash(), from_fields);
+    }
+
+    #[test]
+    fn git_manifest_lets_generic_code_hash_any_node_type_uniformly() {
+        use crate::git_manifest::GitManifest;
+
+        let revision = Revision {
+            directory: [1u8; 20].into(),
+            parents: vec![],
+            author: person(),
+            author_date: timestamp(),
+            committer: person(),
+            committer_date: timestamp(),
+            message: b"msg".to_vec(),
+            extra_headers: vec![],
+            raw_manifest: None,
+        };
+        let release = Release {
+            target: [2u8; 20].into(),
+            target_type: ObjectType::Revision,
+            name: b"v1.0".to_vec(),
+            author: Some(person()),
+            date: Some(timestamp()),
+            message: b"release notes\n".to_vec(),
+            raw_manifest: None,
+        };
+        let snapshot = Snapshot::default();
+
+        let nodes: Vec<&dyn GitManifest> = vec![&revision, &release, &snapshot];
+        let expected = [
+            ("commit", revision.to_git_object(), revision.swhid()),
+            ("tag", release.to_git_object(), release.swhid()),
+            ("snapshot", snapshot.to_manifest(), snapshot.swhid()),
+        ];
+        for (node, (git_type, manifest, swhid)) in nodes.iter().zip(expected) {
+            assert_eq!(node.git_type(), git_type);
+            assert_eq!(node.manifest(), manifest);
+            assert_eq!(node.swhid(), swhid);
+        }
+    }
+
+    #[test]
+    fn builder_produces_headers_in_canonical_order_with_extras_last() {
+        let revision = Revision::builder()
+            .directory([1u8; 20])
+            .parent([2u8; 20])
+            .author(person(), timestamp())
+            .committer(person(), timestamp())
+            .extra_header("mergetag", "object deadbeef\n")
+            .message("msg")
+            .build()
+            .unwrap();
+
+        let body = revision.to_git_object();
+        let text = String::from_utf8_lossy(&body);
+        let tree_at = text.find("tree ").unwrap();
+        let parent_at = text.find("parent ").unwrap();
+        let author_at = text.find("author ").unwrap();
+        let committer_at = text.find("committer ").unwrap();
+        let mergetag_at = text.find("mergetag ").unwrap();
+        assert!(tree_at < parent_at);
+        assert!(parent_at < author_at);
+        assert!(author_at < committer_at);
+        assert!(committer_at < mergetag_at);
+    }
+
+    /// [`RevisionBuilder::directory`]/[`RevisionBuilder::parent`] take
+    /// `impl Into<GitSha1>`, so a caller threading typed [`GitSha1`]s
+    /// through (instead of bare `[u8; 20]`s, as every other test in this
+    /// file does) builds the identical revision.
+    #[test]
+    fn builder_accepts_typed_git_sha1_directly() {
+        let by_array = Revision::builder()
+            .directory([1u8; 20])
+            .parent([2u8; 20])
+            .author(person(), timestamp())
+            .committer(person(), timestamp())
+            .build()
+            .unwrap();
+        let by_git_sha1 = Revision::builder()
+            .directory(GitSha1::new([1u8; 20]))
+            .parent(GitSha1::new([2u8; 20]))
+            .author(person(), timestamp())
+            .committer(person(), timestamp())
+            .build()
+            .unwrap();
+        assert_eq!(by_array.directory, by_git_sha1.directory);
+        assert_eq!(by_array.parents, by_git_sha1.parents);
+        assert_eq!(by_array.swhid(), by_git_sha1.swhid());
+    }
+
+    /// There's no `RevisionType`/origin-VCS enum in this crate — every
+    /// revision hashes via the same git `commit` manifest, with any
+    /// origin-specific convention carried as an ordinary extra header.
+    /// This matrix builds one revision per hypothetical origin and checks
+    /// each yields exactly the manifest `to_git_object` would regenerate,
+    /// with that origin's extra header present and placed last.
+    #[test]
+    fn every_revision_origin_hashes_via_the_same_git_manifest() {
+        let origins: &[(&str, &str, &str)] = &[
+            ("git", "gpgsig", "-----BEGIN PGP SIGNATURE-----\n...\n"),
+            ("mercurial", "hg-changeset", "deadbeefcafebabe"),
+            ("tar", "tar-checksum", "sha256:abcdef"),
+            ("dsc", "dsc-fields", "Source: pkg\nVersion: 1.0\n"),
+        ];
+        for (origin, header_name, header_value) in origins {
+            let revision = Revision::builder()
+                .directory([1u8; 20])
+                .author(person(), timestamp())
+                .committer(person(), timestamp())
+                .extra_header(*header_name, *header_value)
+                .message(format!("imported from {origin}"))
+                .build()
+                .unwrap();
+
+            assert_eq!(
+                revision.compute_hash(),
+                hash_git_object("commit", &revision.to_git_object()),
+                "origin {origin} did not hash via the standard commit manifest"
+            );
+            let text = String::from_utf8_lossy(&revision.to_git_object()).into_owned();
+            let header_at = text.find(&format!("{header_name} ")).unwrap_or_else(|| {
+                panic!("origin {origin}'s extra header {header_name:?} missing from manifest")
+            });
+            let committer_at = text.find("committer ").unwrap();
+            assert!(
+                committer_at < header_at,
+                "origin {origin}'s extra header should come after the fixed headers"
+            );
+        }
+    }
+
+    #[test]
+    fn builder_rejects_a_revision_missing_required_fields() {
+        assert!(Revision::builder().build().is_err());
+        assert!(Revision::builder()
+            .directory([1u8; 20])
+            .author(person(), timestamp())
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn extra_header_with_embedded_newlines_is_folded_with_continuation_spaces() {
+        let revision = Revision::builder()
+            .directory([1u8; 20])
+            .author(person(), timestamp())
+            .committer(person(), timestamp())
+            .extra_header("gpgsig", "-----BEGIN-----\nabc\n-----END-----")
+            .message("msg")
+            .build()
+            .unwrap();
+
+        let body = revision.to_git_object();
+        let text = String::from_utf8_lossy(&body);
+        assert!(text.contains("gpgsig -----BEGIN-----\n abc\n -----END-----\n"));
+    }
+
+    #[test]
+    fn offset_minutes_parses_positive_and_negative_offsets() {
+        assert_eq!(GitTimestamp::new(0, "+0530").offset_minutes().unwrap(), 330);
+        assert_eq!(GitTimestamp::new(0, "-0700").offset_minutes().unwrap(), -420);
+        assert_eq!(GitTimestamp::new(0, "+0000").offset_minutes().unwrap(), 0);
+    }
+
+    #[test]
+    fn offset_minutes_rejects_a_malformed_offset() {
+        assert!(GitTimestamp::new(0, "bogus").offset_minutes().is_err());
+    }
+
+    #[test]
+    fn offset_minutes_rejects_multibyte_input_without_panicking() {
+        // A naive `&offset[0..1]`/`&offset[1..3]` byte-range slice would
+        // panic here since these aren't char boundaries in a 5-byte string
+        // containing a multibyte character.
+        for offset in ["€0530", "+05é0", "+053€", "ñ", "😀😀😀"] {
+            assert!(GitTimestamp::new(0, offset).offset_minutes().is_err());
+        }
+    }
+
+    #[test]
+    fn offset_minutes_rejects_empty_and_oversized_input() {
+        assert!(GitTimestamp::new(0, "").offset_minutes().is_err());
+        assert!(GitTimestamp::new(0, "+".repeat(10_000)).offset_minutes().is_err());
+    }
+
+    #[test]
+    fn to_datetime_with_offset_preserves_the_authored_timezone() {
+        let ts = GitTimestamp::new(1_700_000_000, "+0530");
+        let datetime = ts.to_datetime_with_offset().unwrap();
+        assert_eq!(datetime.offset().local_minus_utc(), 330 * 60);
+        assert_eq!(datetime.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn from_rfc3339_parses_seconds_and_positive_offset() {
+        let ts = GitTimestamp::from_rfc3339("2023-11-14T22:13:20+05:30").unwrap();
+        assert_eq!(ts.seconds, 1_699_980_200);
+        assert_eq!(ts.offset, "+0530");
+        assert_eq!(ts.micros, 0);
+    }
+
+    #[test]
+    fn from_rfc3339_parses_negative_offset_and_fractional_seconds() {
+        let ts = GitTimestamp::from_rfc3339("2023-11-14T11:43:20.123456-07:00").unwrap();
+        assert_eq!(ts.seconds, 1_699_987_400);
+        assert_eq!(ts.offset, "-0700");
+        assert_eq!(ts.micros, 123_456);
+    }
+
+    #[test]
+    fn from_rfc3339_parses_utc_zulu_suffix() {
+        let ts = GitTimestamp::from_rfc3339("2023-11-14T22:13:20Z").unwrap();
+        assert_eq!(ts.seconds, 1_700_000_000);
+        assert_eq!(ts.offset, "+0000");
+    }
+
+    #[test]
+    fn from_rfc3339_round_trips_through_to_datetime_with_offset() {
+        let ts = GitTimestamp::from_rfc3339("2023-11-14T22:13:20+05:30").unwrap();
+        let datetime = ts.to_datetime_with_offset().unwrap();
+        assert_eq!(datetime.timestamp(), 1_699_980_200);
+        assert_eq!(datetime.offset().local_minus_utc(), 330 * 60);
+    }
+
+    #[test]
+    fn from_rfc3339_rejects_malformed_input() {
+        for s in ["", "not a date", "2023-11-14", "2023-11-14T22:13:20"] {
+            assert!(GitTimestamp::from_rfc3339(s).is_err(), "{s:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn to_git_object_independently_folds_multiple_multiline_extra_headers() {
+        let revision = Revision::builder()
+            .directory([1u8; 20])
+            .author(person(), timestamp())
+            .committer(person(), timestamp())
+            .extra_header("gpgsig", "-----BEGIN PGP SIGNATURE-----\n\nabcdef\n-----END PGP SIGNATURE-----")
+            .extra_header(
+                "mergetag",
+                "object 0101010101010101010101010101010101010101\ntype commit\ntag v1.0\n",
+            )
+            .message("Merge tag 'v1.0'\n")
+            .build()
+            .unwrap();
+
+        let body = revision.to_git_object();
+        let expected = b"tree 0101010101010101010101010101010101010101\n\
+author Jane Dev <jane@example.com> 1700000000 +0000\n\
+committer Jane Dev <jane@example.com> 1700000000 +0000\n\
+gpgsig -----BEGIN PGP SIGNATURE-----\n \n abcdef\n -----END PGP SIGNATURE-----\n\
+mergetag object 0101010101010101010101010101010101010101\n type commit\n tag v1.0\n \n\
+\n\
+Merge tag 'v1.0'\n";
+        assert_eq!(body, expected);
+        assert_eq!(revision.compute_hash(), hash_git_object("commit", expected));
+    }
+
+    #[test]
+    fn release_raw_manifest_overrides_regenerated_bytes() {
+        let release = Release {
+            target: [2u8; 20].into(),
+            target_type: ObjectType::Revision,
+            name: b"v1.0".to_vec(),
+            author: Some(person()),
+            date: Some(timestamp()),
+            message: b"release notes\n".to_vec(),
+            raw_manifest: None,
+        };
+        let from_fields = release.compute_hash();
+
+        let quirky_raw = b"object 0202020202020202020202020202020202020202\ntype commit\ntag v1.0\n\nquirky\n".to_vec();
+        let with_raw = Release {
+            raw_manifest: Some(quirky_raw.clone()),
+            ..release
+        };
+        assert_eq!(with_raw.compute_hash(), hash_git_object("tag", &quirky_raw));
+        assert_ne!(with_raw.compute_hash(), from_fields);
+    }
+
+    #[test]
+    fn empty_snapshot_matches_the_well_known_swhid() {
+        let empty = Snapshot::empty();
+        assert!(empty.branches.is_empty());
+        assert_eq!(
+            empty.swhid().to_string(),
+            "swh:1:snp:1a8893e6a86f444e8be8e7bda6cb34fb1735a00e"
+        );
+    }
+
+    #[test]
+    fn snapshot_raw_manifest_overrides_regenerated_bytes() {
+        let mut branches = std::collections::BTreeMap::new();
+        branches.insert(
+            b"refs/heads/main".to_vec(),
+            Some(SnapshotBranch::Object {
+                target: [3u8; 20],
+                target_type: ObjectType::Revision,
+            }),
+        );
+        let snapshot = Snapshot {
+            branches,
+            raw_manifest: None,
+        };
+        let from_fields = snapshot.compute_hash();
+
+        let quirky_raw = b"quirky-manifest".to_vec();
+        let with_raw = Snapshot {
+            raw_manifest: Some(quirky_raw.clone()),
+            ..snapshot
+        };
+        assert_eq!(with_raw.compute_hash(), hash_git_object("snapshot", &quirky_raw));
+        assert_ne!(with_raw.compute_hash(), from_fields);
+    }
+
+    #[test]
+    fn validate_accepts_alias_pointing_at_a_present_branch() {
+        let mut branches = std::collections::BTreeMap::new();
+        branches.insert(
+            b"refs/heads/main".to_vec(),
+            Some(SnapshotBranch::Object {
+                target: [3u8; 20],
+                target_type: ObjectType::Revision,
+            }),
+        );
+        branches.insert(
+            b"HEAD".to_vec(),
+            Some(SnapshotBranch::Alias {
+                target: b"refs/heads/main".to_vec(),
+            }),
+        );
+        let snapshot = Snapshot {
+            branches,
+            raw_manifest: None,
+        };
+        assert!(snapshot.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_alias_pointing_at_a_missing_branch() {
+        let mut branches = std::collections::BTreeMap::new();
+        branches.insert(
+            b"HEAD".to_vec(),
+            Some(SnapshotBranch::Alias {
+                target: b"refs/heads/main".to_vec(),
+            }),
+        );
+        let snapshot = Snapshot {
+            branches,
+            raw_manifest: None,
+        };
+        assert!(snapshot.validate().is_err());
+    }
+
+    #[test]
+    fn resolved_peels_a_multi_hop_alias_to_its_final_object_branch() {
+        let revision_branch = SnapshotBranch::Object {
+            target: [3u8; 20],
+            target_type: ObjectType::Revision,
+        };
+        let mut snapshot = Snapshot::from_refs([])
+            .with_alias("HEAD", "refs/remotes/origin/HEAD")
+            .with_alias("refs/remotes/origin/HEAD", "refs/heads/main");
+        snapshot
+            .branches
+            .insert(b"refs/heads/main".to_vec(), Some(revision_branch.clone()));
+
+        let resolved = snapshot.resolved().unwrap();
+        assert_eq!(resolved[b"HEAD".as_slice()], Some(revision_branch.clone()));
+        assert_eq!(
+            resolved[b"refs/remotes/origin/HEAD".as_slice()],
+            Some(revision_branch.clone())
+        );
+        assert_eq!(resolved[b"refs/heads/main".as_slice()], Some(revision_branch));
+    }
+
+    #[test]
+    fn resolved_reports_a_dangling_alias_as_none() {
+        let snapshot = Snapshot::from_refs([]).with_alias("HEAD", "refs/heads/main");
+        let resolved = snapshot.resolved().unwrap();
+        assert_eq!(resolved[b"HEAD".as_slice()], None);
+    }
+
+    #[test]
+    fn resolved_folds_an_alias_to_a_genuinely_missing_branch_into_none_too() {
+        // `resolved` is a display/export helper and deliberately doesn't
+        // distinguish "alias target present but dangling" from "alias
+        // target absent from this snapshot entirely" — both just become
+        // `None`. `Snapshot::validate` is the strict check that errors on
+        // the latter.
+        let mut snapshot = Snapshot::from_refs([]).with_alias("HEAD", "refs/heads/main");
+        assert!(snapshot.validate().is_err());
+        assert_eq!(snapshot.resolved().unwrap()[b"HEAD".as_slice()], None);
+
+        snapshot.branches.insert(b"refs/heads/main".to_vec(), None);
+        assert!(snapshot.validate().is_ok());
+        assert_eq!(snapshot.resolved().unwrap()[b"HEAD".as_slice()], None);
+    }
+
+    #[test]
+    fn resolved_detects_an_alias_loop() {
+        let snapshot = Snapshot::from_refs([])
+            .with_alias("a", "b")
+            .with_alias("b", "a");
+        assert!(snapshot.resolved().is_err());
+    }
+
+    #[test]
+    fn revision_verify_accepts_none_and_matching_raw_manifest_but_rejects_a_mismatch() {
+        let revision = Revision {
+            directory: [1u8; 20].into(),
+            parents: vec![],
+            author: person(),
+            author_date: timestamp(),
+            committer: person(),
+            committer_date: timestamp(),
+            message: b"msg".to_vec(),
+            extra_headers: vec![],
+            raw_manifest: None,
+        };
+        assert!(revision.verify());
+
+        let matching = Revision {
+            raw_manifest: Some(revision.to_git_object()),
+            ..revision.clone()
+        };
+        assert!(matching.verify());
+
+        let corrupted = Revision {
+            raw_manifest: Some(b"tree 0101010101010101010101010101010101010101\n\nquirky\n".to_vec()),
+            ..revision
+        };
+        assert!(!corrupted.verify());
+        assert!(corrupted.verify_self_consistent().is_err());
+    }
+
+    #[test]
+    fn release_verify_accepts_none_and_matching_raw_manifest_but_rejects_a_mismatch() {
+        let release = Release {
+            target: [2u8; 20].into(),
+            target_type: ObjectType::Revision,
+            name: b"v1.0".to_vec(),
+            author: Some(person()),
+            date: Some(timestamp()),
+            message: b"release notes\n".to_vec(),
+            raw_manifest: None,
+        };
+        assert!(release.verify());
+
+        let matching = Release {
+            raw_manifest: Some(release.to_git_object()),
+            ..release.clone()
+        };
+        assert!(matching.verify());
+
+        let corrupted = Release {
+            raw_manifest: Some(b"object 0202020202020202020202020202020202020202\ntype commit\ntag v1.0\n\nquirky\n".to_vec()),
+            ..release
+        };
+        assert!(!corrupted.verify());
+    }
+
+    #[test]
+    fn snapshot_verify_accepts_none_and_matching_raw_manifest_but_rejects_a_mismatch() {
+        let mut branches = std::collections::BTreeMap::new();
+        branches.insert(
+            b"refs/heads/main".to_vec(),
+            Some(SnapshotBranch::Object {
+                target: [3u8; 20],
+                target_type: ObjectType::Revision,
+            }),
+        );
+        let snapshot = Snapshot {
+            branches,
+            raw_manifest: None,
+        };
+        assert!(snapshot.verify());
+
+        let matching = Snapshot {
+            raw_manifest: Some(snapshot.to_manifest()),
+            ..snapshot.clone()
+        };
+        assert!(matching.verify());
+
+        let corrupted = Snapshot {
+            raw_manifest: Some(b"quirky-manifest".to_vec()),
+            ..snapshot
+        };
+        assert!(!corrupted.verify());
+    }
+
+    #[test]
+    fn person_to_dict_from_dict_round_trips() {
+        let original = person();
+        let restored = Person::from_dict(&original.to_dict()).unwrap();
+        assert_eq!(original, restored);
+    }
+
+    #[test]
+    fn from_raw_preserves_a_pathological_fullname_with_more_than_one_bracket_pair() {
+        let pathological: Vec<u8> = b"Weird <Name> <jane@example.com>".to_vec();
+        let person = Person::from_raw(pathological.clone());
+
+        assert_eq!(person.fullname(), pathological);
+        // The best-effort split still picks the last `<...>` as the email.
+        assert_eq!(person.email, b"jane@example.com");
+        assert_eq!(person.name, b"Weird <Name>");
+    }
+
+    #[test]
+    fn from_raw_fullname_is_byte_identical_in_a_hashed_revision() {
+        let pathological: Vec<u8> = b"A <B> <C <D> E>".to_vec();
+        let via_raw = Person::from_raw(pathological.clone());
+
+        let revision = Revision {
+            author: via_raw,
+            author_date: timestamp(),
+            committer: person(),
+            committer_date: timestamp(),
+            message: b"msg".to_vec(),
+            parents: Vec::new(),
+            directory: [0u8; 20].into(),
+            extra_headers: Vec::new(),
+            raw_manifest: None,
+        };
+        assert!(revision.to_git_object().windows(pathological.len()).any(|w| w == pathological.as_slice()));
+    }
+
+    #[test]
+    fn git_timestamp_to_dict_from_dict_round_trips() {
+        let original = timestamp().with_micros(42);
+        let restored = GitTimestamp::from_dict(&original.to_dict()).unwrap();
+        assert_eq!(original, restored);
+    }
+
+    #[test]
+    fn revision_to_dict_from_dict_round_trips_and_preserves_the_swhid() {
+        let revision = Revision {
+            directory: [1u8; 20].into(),
+            parents: vec![[2u8; 20].into(), [3u8; 20].into()],
+            author: person(),
+            author_date: timestamp(),
+            committer: person(),
+            committer_date: timestamp(),
+            message: b"a commit message\nwith a newline".to_vec(),
+            extra_headers: vec![(b"gpgsig".to_vec(), b"-----BEGIN-----\n...".to_vec())],
+            raw_manifest: None,
+        };
+        let restored = Revision::from_dict(&revision.to_dict()).unwrap();
+        assert_eq!(restored, revision);
+        assert_eq!(restored.swhid(), revision.swhid());
+    }
+
+    #[test]
+    fn release_to_dict_from_dict_round_trips_with_and_without_a_tagger() {
+        let release = Release {
+            target: [4u8; 20].into(),
+            target_type: ObjectType::Revision,
+            name: b"v1.0".to_vec(),
+            author: Some(person()),
+            date: Some(timestamp()),
+            message: b"release notes".to_vec(),
+            raw_manifest: None,
+        };
+        let restored = Release::from_dict(&release.to_dict()).unwrap();
+        assert_eq!(restored, release);
+        assert_eq!(restored.swhid(), release.swhid());
+
+        let lightweight = Release {
+            author: None,
+            date: None,
+            ..release
+        };
+        let restored_lightweight = Release::from_dict(&lightweight.to_dict()).unwrap();
+        assert_eq!(restored_lightweight, lightweight);
+    }
+
+    #[test]
+    fn snapshot_to_dict_from_dict_round_trips_including_aliases_and_dangling_branches() {
+        let mut branches = std::collections::BTreeMap::new();
+        branches.insert(
+            b"refs/heads/main".to_vec(),
+            Some(SnapshotBranch::Object {
+                target: [3u8; 20],
+                target_type: ObjectType::Revision,
+            }),
+        );
+        branches.insert(
+            b"HEAD".to_vec(),
+            Some(SnapshotBranch::Alias {
+                target: b"refs/heads/main".to_vec(),
+            }),
+        );
+        branches.insert(b"refs/heads/dangling".to_vec(), None);
+        let snapshot = Snapshot {
+            branches,
+            raw_manifest: None,
+        };
+        let restored = Snapshot::from_dict(&snapshot.to_dict()).unwrap();
+        assert_eq!(restored, snapshot);
+        assert_eq!(restored.swhid(), snapshot.swhid());
+    }
+
+    #[test]
+    fn from_dict_rejects_a_malformed_revision() {
+        assert!(Revision::from_dict(&serde_json::json!({})).is_err());
+        assert!(Revision::from_dict(&serde_json::json!({"directory": "not hex"})).is_err());
+    }
+
+    #[test]
+    fn infer_ref_target_type_treats_tags_as_releases_and_everything_else_as_revisions() {
+        assert_eq!(
+            infer_ref_target_type(b"refs/tags/v1.0"),
+            ObjectType::Release
+        );
+        assert_eq!(
+            infer_ref_target_type(b"refs/heads/main"),
+            ObjectType::Revision
+        );
+        assert_eq!(infer_ref_target_type(b"HEAD"), ObjectType::Revision);
+    }
+
+    #[test]
+    fn snapshot_from_refs_infers_types_and_with_alias_adds_a_symbolic_branch() {
+        let head_target = [0x11; 20];
+        let tag_target = [0x22; 20];
+        let snapshot = Snapshot::from_refs(vec![
+            (b"refs/heads/main".to_vec(), head_target),
+            (b"refs/tags/v1.0".to_vec(), tag_target),
+        ])
+        .with_alias(b"HEAD".to_vec(), b"refs/heads/main".to_vec());
+
+        assert_eq!(
+            snapshot.branches.get(b"refs/heads/main".as_slice()),
+            Some(&Some(SnapshotBranch::Object {
+                target: head_target,
+                target_type: ObjectType::Revision,
+            }))
+        );
+        assert_eq!(
+            snapshot.branches.get(b"refs/tags/v1.0".as_slice()),
+            Some(&Some(SnapshotBranch::Object {
+                target: tag_target,
+                target_type: ObjectType::Release,
+            }))
+        );
+        assert_eq!(
+            snapshot.branches.get(b"HEAD".as_slice()),
+            Some(&Some(SnapshotBranch::Alias {
+                target: b"refs/heads/main".to_vec(),
+            }))
+        );
+        snapshot.validate().unwrap();
+    }
+}
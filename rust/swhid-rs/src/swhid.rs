@@ -0,0 +1,738 @@
+use core::fmt;
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, string::ToString, vec::Vec};
+
+use crate::error::SwhidError;
+
+/// The five object kinds a SWHID can identify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+    Content,
+    Directory,
+    Revision,
+    Release,
+    Snapshot,
+}
+
+impl ObjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectType::Content => "cnt",
+            ObjectType::Directory => "dir",
+            ObjectType::Revision => "rev",
+            ObjectType::Release => "rel",
+            ObjectType::Snapshot => "snp",
+        }
+    }
+
+    /// The git object type (`blob`/`tree`/`commit`/`tag`) this kind of
+    /// object corresponds to, for interop with `git cat-file -t` and
+    /// friends. `Snapshot` has no git equivalent, so it maps to `None`.
+    pub fn git_type(&self) -> Option<&'static str> {
+        match self {
+            ObjectType::Content => Some("blob"),
+            ObjectType::Directory => Some("tree"),
+            ObjectType::Revision => Some("commit"),
+            ObjectType::Release => Some("tag"),
+            ObjectType::Snapshot => None,
+        }
+    }
+
+    pub fn from_str_code(s: &str) -> Result<Self, SwhidError> {
+        match s {
+            "cnt" => Ok(ObjectType::Content),
+            "dir" => Ok(ObjectType::Directory),
+            "rev" => Ok(ObjectType::Revision),
+            "rel" => Ok(ObjectType::Release),
+            "snp" => Ok(ObjectType::Snapshot),
+            other => Err(SwhidError::InvalidSwhid(format!(
+                "unknown object type code: {other}"
+            ))),
+        }
+    }
+}
+
+/// A Software Heritage persistent identifier: `swh:1:<type>:<hex sha1>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Swhid {
+    namespace: &'static str,
+    object_type: ObjectType,
+    hash: [u8; 20],
+}
+
+impl Swhid {
+    pub const NAMESPACE: &'static str = "swh";
+    pub const VERSION: u32 = 1;
+
+    /// git's well-known empty blob hash: `e69de29b...`, the sha1 of
+    /// `"blob 0\0"` with no data.
+    const EMPTY_CONTENT_HASH: [u8; 20] = [
+        0xe6, 0x9d, 0xe2, 0x9b, 0xb2, 0xd1, 0xd6, 0x43, 0x4b, 0x8b, 0x29, 0xae, 0x77, 0x5a, 0xd8,
+        0xc2, 0xe4, 0x8c, 0x53, 0x91,
+    ];
+
+    /// git's well-known empty tree hash: `4b825dc6...`, the sha1 of
+    /// `"tree 0\0"` with no entries.
+    const EMPTY_DIRECTORY_HASH: [u8; 20] = [
+        0x4b, 0x82, 0x5d, 0xc6, 0x42, 0xcb, 0x6e, 0xb9, 0xa0, 0x60, 0xe5, 0x4b, 0xf8, 0xd6, 0x92,
+        0x88, 0xfb, 0xee, 0x49, 0x04,
+    ];
+
+    /// The SWHID of the empty content, `swh:1:cnt:e69de29b...`.
+    pub fn empty_content() -> Self {
+        Swhid::new(ObjectType::Content, Self::EMPTY_CONTENT_HASH)
+    }
+
+    /// The SWHID of the empty directory, `swh:1:dir:4b825dc6...`.
+    pub fn empty_directory() -> Self {
+        Swhid::new(ObjectType::Directory, Self::EMPTY_DIRECTORY_HASH)
+    }
+
+    pub fn new(object_type: ObjectType, hash: [u8; 20]) -> Self {
+        Swhid {
+            namespace: Self::NAMESPACE,
+            object_type,
+            hash,
+        }
+    }
+
+    /// Like [`Swhid::new`], but under a custom namespace instead of the
+    /// spec-mandated `"swh"`. Meant for forks and test harnesses that need
+    /// to round-trip identifiers in a differently-namespaced compatibility
+    /// layer; normal callers should stick to [`Swhid::new`].
+    pub fn new_with_namespace(
+        namespace: &'static str,
+        object_type: ObjectType,
+        hash: [u8; 20],
+    ) -> Self {
+        Swhid {
+            namespace,
+            object_type,
+            hash,
+        }
+    }
+
+    /// The namespace this SWHID was built under: `"swh"` unless it came
+    /// from [`Swhid::new_with_namespace`] or
+    /// [`Swhid::from_string_with_namespace`].
+    pub fn namespace(&self) -> &'static str {
+        self.namespace
+    }
+
+    pub fn object_type(&self) -> ObjectType {
+        self.object_type
+    }
+
+    pub fn hash(&self) -> &[u8; 20] {
+        &self.hash
+    }
+
+    /// The 40-char lowercase hex encoding of the object id, i.e. `hex::encode(swhid.hash())`
+    /// without pulling in `hex` at call sites.
+    pub fn hash_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+
+    /// The raw 20-byte object id, as a slice.
+    pub fn object_id_bytes(&self) -> &[u8] {
+        &self.hash
+    }
+
+    /// Write the canonical `swh:1:<type>:<hex>` form directly to `w`,
+    /// without allocating the intermediate `String` that [`Swhid::Display`]
+    /// would otherwise need `hex::encode` to produce. [`Swhid::Display`] is
+    /// implemented in terms of this, so the two always agree byte-for-byte;
+    /// call this directly when formatting many SWHIDs into one buffered
+    /// writer and the extra allocation per SWHID would add up.
+    pub fn write_to(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        write!(w, "{}:{}:{}:", self.namespace, Self::VERSION, self.object_type.as_str())?;
+        for byte in self.hash {
+            write!(w, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+
+    /// Parse a `swh:1:<type>:<hex>` string. The hash part accepts mixed or
+    /// uppercase hex digits (`hex::decode` is case-insensitive); since the
+    /// hash is normalized to raw bytes, two `Swhid`s that differ only in the
+    /// hex case of their source string still compare equal. `Display`
+    /// always renders lowercase, so parsing an uppercase SWHID and printing
+    /// it back does not round-trip byte-for-byte, only value-for-value.
+    ///
+    /// Strict: a trailing `;key=value` qualifier suffix (valid in a SWHID
+    /// URL) is rejected, since it's not part of the core identifier this
+    /// type represents. Use [`Swhid::from_qualified_string`] to accept and
+    /// discard qualifiers, or [`Swhid::from_url`] to keep them.
+    pub fn from_string(s: &str) -> Result<Self, SwhidError> {
+        Self::from_string_with_namespace(Self::NAMESPACE, s)
+    }
+
+    /// Like [`Swhid::from_string`], but tolerates (and discards) a trailing
+    /// `;key=value` qualifier suffix, e.g. `swh:1:cnt:<hex>;path=/a/b`. Use
+    /// [`Swhid::from_url`] instead if the qualifiers themselves are needed.
+    pub fn from_qualified_string(s: &str) -> Result<Self, SwhidError> {
+        let core = s.split(';').next().unwrap_or(s);
+        Self::from_string(core)
+    }
+
+    /// Like [`Swhid::from_string`], but requiring `namespace` instead of
+    /// the spec-mandated `"swh"`, and tagging the resulting `Swhid` with it
+    /// (see [`Swhid::namespace`]). Meant for forks and test harnesses that
+    /// use a differently-namespaced compatibility layer; normal callers
+    /// should stick to [`Swhid::from_string`], which stays strict.
+    pub fn from_string_with_namespace(namespace: &'static str, s: &str) -> Result<Self, SwhidError> {
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() != 4 {
+            return Err(SwhidError::InvalidSwhid(s.to_string()));
+        }
+        let [found_namespace, version, type_code, hex_hash] =
+            [parts[0], parts[1], parts[2], parts[3]];
+        if found_namespace != namespace {
+            return Err(SwhidError::InvalidSwhid(s.to_string()));
+        }
+        if version != "1" {
+            return Err(SwhidError::InvalidSwhid(s.to_string()));
+        }
+        let object_type = ObjectType::from_str_code(type_code)
+            .map_err(|_| SwhidError::InvalidSwhid(s.to_string()))?;
+        if hex_hash.len() != 40 {
+            return Err(SwhidError::InvalidSwhid(s.to_string()));
+        }
+        let bytes = hex::decode(hex_hash).map_err(|_| {
+            let position = hex_hash
+                .char_indices()
+                .find(|(_, c)| !c.is_ascii_hexdigit())
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            SwhidError::InvalidHash {
+                input: hex_hash.to_string(),
+                position,
+            }
+        })?;
+        let hash: [u8; 20] = bytes
+            .try_into()
+            .map_err(|_| SwhidError::InvalidSwhid(s.to_string()))?;
+        Ok(Swhid {
+            namespace,
+            object_type,
+            hash,
+        })
+    }
+
+    /// Build a `Swhid` from a type code (`"cnt"`, `"dir"`, ...) and a raw
+    /// 20-byte object id, as found in a database row. Rejects an `id` of the
+    /// wrong length or an unrecognized `type_code`.
+    pub fn from_parts(type_code: &str, id: &[u8]) -> Result<Self, SwhidError> {
+        let object_type = ObjectType::from_str_code(type_code)?;
+        let hash: [u8; 20] = id.try_into().map_err(|_| {
+            SwhidError::InvalidSwhid(format!(
+                "object id must be 20 bytes, got {}",
+                id.len()
+            ))
+        })?;
+        Ok(Swhid {
+            namespace: Self::NAMESPACE,
+            object_type,
+            hash,
+        })
+    }
+
+    /// Parse every SWHID in `text`, one result per token found. Blank lines
+    /// and lines starting with `#` (after trimming) are skipped entirely;
+    /// every other line is split on whitespace and each token is parsed
+    /// independently, so a malformed token doesn't prevent the rest of
+    /// `text` from being parsed. [`SwhidError::InvalidSwhid`]/
+    /// [`SwhidError::InvalidHash`] carry the offending token, so callers
+    /// pairing this with `text.lines()` can still report which line failed.
+    pub fn parse_many(text: &str) -> Vec<Result<Self, SwhidError>> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .flat_map(str::split_whitespace)
+            .map(Self::from_string)
+            .collect()
+    }
+
+    /// Whether `s` would parse with [`Swhid::from_string`], without
+    /// constructing the `Swhid` or an error. Useful for cheaply filtering a
+    /// stream of candidate strings.
+    pub fn is_valid(s: &str) -> bool {
+        Self::from_string(s).is_ok()
+    }
+
+    /// Extract and parse a SWHID, with any `;key=value` qualifiers, embedded
+    /// in a Software Heritage browse URL, e.g.
+    /// `https://archive.softwareheritage.org/swh:1:dir:<hex>;origin=...`.
+    /// Anything before the `swh:1:` prefix (scheme, host, path) is ignored,
+    /// as is any `?query` or `#fragment` suffix. Returns
+    /// [`SwhidError::InvalidSwhid`] if the URL contains no recognizable
+    /// SWHID.
+    pub fn from_url(url: &str) -> Result<QualifiedSwhid, SwhidError> {
+        const PREFIX: &str = "swh:1:";
+        let start = url
+            .find(PREFIX)
+            .ok_or_else(|| SwhidError::InvalidSwhid(url.to_string()))?;
+        let rest = &url[start..];
+        let rest = rest.split(['?', '#']).next().unwrap_or(rest);
+        let mut segments = rest.split(';');
+        let core = segments.next().unwrap_or("");
+        let swhid = Swhid::from_string(core)?;
+
+        let mut qualifiers = Vec::new();
+        for segment in segments {
+            if segment.is_empty() {
+                continue;
+            }
+            let (key, value) = segment.split_once('=').ok_or_else(|| {
+                SwhidError::InvalidSwhid(format!(
+                    "malformed qualifier {segment:?} in SWHID URL {url:?}"
+                ))
+            })?;
+            qualifiers.push((key.to_string(), value.to_string()));
+        }
+        Ok(QualifiedSwhid { swhid, qualifiers })
+    }
+}
+
+/// A [`Swhid`] together with the `;key=value` qualifiers (`origin`,
+/// `anchor`, `path`, `lines`, ...) found alongside it, as produced by
+/// [`Swhid::from_url`]. Qualifiers are kept in the order they appeared in
+/// the URL, with no interpretation of their keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualifiedSwhid {
+    pub swhid: Swhid,
+    pub qualifiers: Vec<(String, String)>,
+}
+
+impl QualifiedSwhid {
+    /// The value of the first qualifier named `key`, if present.
+    pub fn qualifier(&self, key: &str) -> Option<&str> {
+        self.qualifiers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// The parsed-out parts of a [`Swhid`], for tooling that wants to inspect or
+/// display them independently instead of re-splitting the display string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwhidComponents {
+    pub namespace: &'static str,
+    pub version: u32,
+    pub type_code: &'static str,
+    pub hash_hex: String,
+}
+
+impl Swhid {
+    /// Break this SWHID down into its namespace, version, type code and hex
+    /// hash, in one call.
+    pub fn components(&self) -> SwhidComponents {
+        SwhidComponents {
+            namespace: self.namespace,
+            version: Self::VERSION,
+            type_code: self.object_type.as_str(),
+            hash_hex: hex::encode(self.hash),
+        }
+    }
+}
+
+impl fmt::Display for Swhid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
+    }
+}
+
+/// Compares against the canonical `swh:1:<type>:<hash>` display string,
+/// case-insensitively (so an uppercase hex hash still compares equal, as
+/// with [`Swhid::from_string`]), rather than parsing the right-hand side —
+/// a malformed string just compares unequal instead of panicking or being
+/// rejected some other way.
+impl PartialEq<str> for Swhid {
+    fn eq(&self, other: &str) -> bool {
+        self.to_string().eq_ignore_ascii_case(other)
+    }
+}
+
+impl PartialEq<Swhid> for str {
+    fn eq(&self, other: &Swhid) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<&str> for Swhid {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<Swhid> for &str {
+    fn eq(&self, other: &Swhid) -> bool {
+        other == *self
+    }
+}
+
+impl PartialEq<String> for Swhid {
+    fn eq(&self, other: &String) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialEq<Swhid> for String {
+    fn eq(&self, other: &Swhid) -> bool {
+        other == self.as_str()
+    }
+}
+
+/// Delegates to [`Swhid::from_string`], for generic code that works in
+/// terms of `TryInto`/`TryFrom` (e.g. a `serde` adapter) rather than a
+/// crate-specific constructor.
+impl TryFrom<&str> for Swhid {
+    type Error = SwhidError;
+
+    fn try_from(s: &str) -> Result<Self, SwhidError> {
+        Self::from_string(s)
+    }
+}
+
+/// Delegates to [`Swhid::fmt`]'s `Display` implementation.
+impl From<Swhid> for String {
+    fn from(swhid: Swhid) -> String {
+        swhid.to_string()
+    }
+}
+
+impl From<&Swhid> for String {
+    fn from(swhid: &Swhid) -> String {
+        swhid.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let swhid = Swhid::new(ObjectType::Content, [0u8; 20]);
+        let s = swhid.to_string();
+        assert_eq!(s, "swh:1:cnt:0000000000000000000000000000000000000000");
+        assert_eq!(Swhid::from_string(&s).unwrap(), swhid);
+    }
+
+    #[test]
+    fn compares_equal_to_its_canonical_string_form_case_insensitively() {
+        let swhid = Swhid::new(ObjectType::Content, [0u8; 20]);
+        assert_eq!(swhid, "swh:1:cnt:0000000000000000000000000000000000000000");
+        assert_eq!(
+            swhid,
+            "SWH:1:CNT:0000000000000000000000000000000000000000".to_string()
+        );
+        assert_ne!(swhid, "swh:1:dir:0000000000000000000000000000000000000000");
+        assert_eq!("swh:1:cnt:0000000000000000000000000000000000000000", swhid);
+    }
+
+    #[test]
+    fn try_from_str_and_from_swhid_for_string_round_trip() {
+        let swhid = Swhid::new(ObjectType::Content, [0u8; 20]);
+        let text = "swh:1:cnt:0000000000000000000000000000000000000000";
+
+        let parsed: Swhid = text.try_into().unwrap();
+        assert_eq!(parsed, swhid);
+        assert!(Swhid::try_from("not a swhid").is_err());
+
+        let owned: String = swhid.into();
+        assert_eq!(owned, text);
+        let borrowed: String = (&swhid).into();
+        assert_eq!(borrowed, text);
+    }
+
+    #[test]
+    fn from_string_rejects_a_custom_namespace_by_default() {
+        assert!(Swhid::from_string("swhtest:1:cnt:0000000000000000000000000000000000000000").is_err());
+    }
+
+    #[test]
+    fn new_with_namespace_and_from_string_with_namespace_round_trip() {
+        let swhid = Swhid::new_with_namespace("swhtest", ObjectType::Content, [0u8; 20]);
+        assert_eq!(swhid.namespace(), "swhtest");
+        let s = swhid.to_string();
+        assert_eq!(s, "swhtest:1:cnt:0000000000000000000000000000000000000000");
+
+        let parsed = Swhid::from_string_with_namespace("swhtest", &s).unwrap();
+        assert_eq!(parsed, swhid);
+        assert!(Swhid::from_string(&s).is_err());
+        assert!(Swhid::from_string_with_namespace("other", &s).is_err());
+    }
+
+    #[test]
+    fn new_defaults_to_the_strict_swh_namespace() {
+        assert_eq!(Swhid::new(ObjectType::Content, [0u8; 20]).namespace(), "swh");
+    }
+
+    #[test]
+    fn parse_many_skips_blank_and_comment_lines_and_reports_per_token_errors() {
+        let text = "\
+# a comment
+swh:1:cnt:0000000000000000000000000000000000000000
+
+not-a-swhid
+swh:1:dir:4b825dc642cb6eb9a060e54bf8d69288fbee4904 swh:1:rel:0000000000000000000000000000000000000000
+";
+        let results = Swhid::parse_many(text);
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+        assert!(results[3].is_ok());
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert!(Swhid::from_string("not-a-swhid").is_err());
+        assert!(Swhid::from_string("swh:2:cnt:00").is_err());
+    }
+
+    #[test]
+    fn from_string_accepts_uppercase_hex_and_compares_equal_to_lowercase() {
+        let lower = Swhid::from_string("swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391").unwrap();
+        let upper = Swhid::from_string("swh:1:cnt:E69DE29BB2D1D6434B8B29AE775AD8C2E48C5391").unwrap();
+        let mixed = Swhid::from_string("swh:1:cnt:e69DE29bB2d1D6434b8B29ae775AD8c2e48C5391").unwrap();
+        assert_eq!(lower, upper);
+        assert_eq!(lower, mixed);
+        assert_eq!(upper.to_string(), lower.to_string());
+    }
+
+    #[test]
+    fn from_string_rejects_a_trailing_qualifier_suffix() {
+        assert!(Swhid::from_string(
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391;path=/a/b"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn from_qualified_string_discards_a_trailing_qualifier_suffix() {
+        let expected =
+            Swhid::from_string("swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391").unwrap();
+        assert_eq!(
+            Swhid::from_qualified_string(
+                "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391;path=/a/b;origin=x"
+            )
+            .unwrap(),
+            expected
+        );
+        // No qualifiers at all still works.
+        assert_eq!(
+            Swhid::from_qualified_string("swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn from_qualified_string_still_rejects_a_malformed_core() {
+        assert!(Swhid::from_qualified_string("not-a-swhid;path=/a/b").is_err());
+    }
+
+    #[test]
+    fn from_url_extracts_the_swhid_and_qualifiers() {
+        let qualified = Swhid::from_url(
+            "https://archive.softwareheritage.org/swh:1:dir:4b825dc642cb6eb9a060e54bf8d69288fbee4904;origin=https://example.org/repo;path=/src",
+        )
+        .unwrap();
+        assert_eq!(
+            qualified.swhid,
+            Swhid::from_string("swh:1:dir:4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap()
+        );
+        assert_eq!(
+            qualified.qualifier("origin"),
+            Some("https://example.org/repo")
+        );
+        assert_eq!(qualified.qualifier("path"), Some("/src"));
+        assert_eq!(qualified.qualifier("anchor"), None);
+    }
+
+    #[test]
+    fn from_url_ignores_a_trailing_fragment_and_works_with_no_qualifiers() {
+        let qualified =
+            Swhid::from_url("https://example.org/swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391#L10")
+                .unwrap();
+        assert_eq!(qualified.swhid, Swhid::empty_content());
+        assert!(qualified.qualifiers.is_empty());
+    }
+
+    #[test]
+    fn from_url_rejects_a_url_with_no_swhid() {
+        assert!(Swhid::from_url("https://example.org/not-a-swhid").is_err());
+    }
+
+    #[test]
+    fn git_type_maps_every_type_except_snapshot() {
+        assert_eq!(ObjectType::Content.git_type(), Some("blob"));
+        assert_eq!(ObjectType::Directory.git_type(), Some("tree"));
+        assert_eq!(ObjectType::Revision.git_type(), Some("commit"));
+        assert_eq!(ObjectType::Release.git_type(), Some("tag"));
+        assert_eq!(ObjectType::Snapshot.git_type(), None);
+    }
+
+    #[test]
+    fn from_parts_matches_from_string() {
+        let swhid =
+            Swhid::from_string("swh:1:dir:4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let rebuilt = Swhid::from_parts("dir", swhid.object_id_bytes()).unwrap();
+        assert_eq!(rebuilt, swhid);
+    }
+
+    #[test]
+    fn from_parts_rejects_wrong_length_id() {
+        assert!(Swhid::from_parts("cnt", &[0u8; 19]).is_err());
+    }
+
+    #[test]
+    fn from_parts_rejects_unknown_type_code() {
+        assert!(Swhid::from_parts("bogus", &[0u8; 20]).is_err());
+    }
+
+    #[test]
+    fn empty_content_and_directory_constants_match_git() {
+        assert_eq!(
+            crate::content::Content::from_data(Vec::new()).swhid(),
+            Swhid::empty_content()
+        );
+        assert_eq!(
+            Swhid::empty_content().to_string(),
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        );
+        assert_eq!(
+            Swhid::empty_directory().to_string(),
+            "swh:1:dir:4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+        );
+    }
+
+    #[test]
+    fn hash_hex_and_object_id_bytes_match_hash() {
+        let swhid =
+            Swhid::from_string("swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391").unwrap();
+        assert_eq!(swhid.hash_hex(), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        assert_eq!(swhid.object_id_bytes(), swhid.hash());
+    }
+
+    #[test]
+    fn is_valid_agrees_with_from_string() {
+        assert!(Swhid::is_valid(
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"
+        ));
+        assert!(!Swhid::is_valid("not-a-swhid"));
+        assert!(!Swhid::is_valid("swh:2:cnt:00"));
+    }
+
+    #[test]
+    fn from_string_reports_the_position_of_the_first_bad_hex_character() {
+        let err =
+            Swhid::from_string("swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c53zz").unwrap_err();
+        match err {
+            SwhidError::InvalidHash { input, position } => {
+                assert_eq!(input, "e69de29bb2d1d6434b8b29ae775ad8c2e48c53zz");
+                assert_eq!(position, 38);
+            }
+            other => panic!("expected InvalidHash, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn from_string_never_panics_on_empty_or_malformed_segments() {
+        for s in [
+            "",
+            ":",
+            ":::",
+            "swh:1:cnt:",
+            "swh::cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "swh:1::e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "swh:1:cnt",
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391:extra",
+        ] {
+            assert!(Swhid::from_string(s).is_err(), "{s:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn from_string_never_panics_on_non_ascii_or_multibyte_input() {
+        // Multibyte characters placed right at the boundaries a naive
+        // byte-offset parser would slice on (namespace/version/type/hash)
+        // must be rejected, not panic the process.
+        for s in [
+            "swh:1:cnt:€69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "swh:€:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "swh:1:€€€:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "swh€1€cnt€e69de29bb2d1d6434b8b29ae775ad8c2e48c5391",
+            "😀😀😀😀😀😀😀😀😀😀",
+            "swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c539€",
+        ] {
+            assert!(Swhid::from_string(s).is_err(), "{s:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn from_string_never_panics_on_extremely_long_input() {
+        let long_hex = "a".repeat(10_000);
+        assert!(Swhid::from_string(&format!("swh:1:cnt:{long_hex}")).is_err());
+
+        let long_garbage = "x".repeat(100_000);
+        assert!(Swhid::from_string(&long_garbage).is_err());
+
+        let many_colons = ":".repeat(50_000);
+        assert!(Swhid::from_string(&many_colons).is_err());
+    }
+
+    #[test]
+    fn write_to_matches_display_byte_for_byte() {
+        let swhid = Swhid::from_string("swh:1:dir:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391").unwrap();
+        let mut written = String::new();
+        swhid.write_to(&mut written).unwrap();
+        assert_eq!(written, swhid.to_string());
+    }
+
+    #[test]
+    fn write_to_works_with_a_non_allocating_fmt_write_target() {
+        // A fixed-capacity buffer implementing `fmt::Write` without ever
+        // touching the heap, to prove `write_to` doesn't secretly require
+        // `String`/`hex::encode` under the hood.
+        struct FixedBuf {
+            data: [u8; 64],
+            len: usize,
+        }
+        impl fmt::Write for FixedBuf {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                let bytes = s.as_bytes();
+                self.data[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+                self.len += bytes.len();
+                Ok(())
+            }
+        }
+
+        let swhid = Swhid::from_string("swh:1:cnt:0000000000000000000000000000000000000000").unwrap();
+        let mut buf = FixedBuf { data: [0; 64], len: 0 };
+        swhid.write_to(&mut buf).unwrap();
+        assert_eq!(
+            core::str::from_utf8(&buf.data[..buf.len]).unwrap(),
+            "swh:1:cnt:0000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn components_break_down_a_parsed_content_swhid() {
+        let swhid =
+            Swhid::from_string("swh:1:cnt:e69de29bb2d1d6434b8b29ae775ad8c2e48c5391").unwrap();
+        assert_eq!(
+            swhid.components(),
+            SwhidComponents {
+                namespace: "swh",
+                version: 1,
+                type_code: "cnt",
+                hash_hex: "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391".to_string(),
+            }
+        );
+    }
+}
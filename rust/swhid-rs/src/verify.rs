@@ -0,0 +1,13 @@
+use crate::error::SwhidError;
+
+/// Implemented by objects that can be parsed from raw bytes and whose
+/// canonical re-encoding can therefore drift from what was originally
+/// stored (e.g. a non-canonical mode string, or reordered fields).
+///
+/// `verify_self_consistent` recomputes the canonical form from the parsed
+/// fields and compares it against the bytes the object was parsed from,
+/// flagging objects that need their `raw_manifest` preserved to reproduce
+/// their original id.
+pub trait SelfConsistent {
+    fn verify_self_consistent(&self) -> Result<(), SwhidError>;
+}
@@ -0,0 +1,102 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn dash_reads_content_from_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swhid"))
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run swhid binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"hello\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout.trim(),
+        swhid::Content::from_data(b"hello\n".to_vec())
+            .swhid()
+            .to_string()
+    );
+}
+
+#[cfg(not(feature = "tar"))]
+#[test]
+fn dash_with_type_dir_errors_clearly_without_the_tar_feature() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swhid"))
+        .arg("-")
+        .arg("--type")
+        .arg("dir")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run swhid binary");
+    child.stdin.take().unwrap().write_all(b"").unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("tar"), "unexpected stderr: {stderr}");
+}
+
+#[cfg(feature = "tar")]
+#[test]
+fn dash_with_type_dir_hashes_a_tar_stream_from_stdin() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"hello\n").unwrap();
+
+    let tar_output = Command::new("tar")
+        .arg("-C")
+        .arg(dir.path())
+        .arg("-c")
+        .arg(".")
+        .output()
+        .expect("failed to run tar");
+    assert!(tar_output.status.success());
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swhid"))
+        .arg("-")
+        .arg("--type")
+        .arg("dir")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run swhid binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&tar_output.stdout)
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let expected = swhid::SwhidComputer::new()
+        .compute_tar_swhid(tar_output.stdout.as_slice())
+        .unwrap();
+    assert_eq!(stdout.trim(), expected.to_string());
+}
+
+#[test]
+fn json_flag_emits_parseable_error_object_for_missing_path() {
+    let output = Command::new(env!("CARGO_BIN_EXE_swhid"))
+        .arg("--json")
+        .arg("/does/not/exist/at/all")
+        .output()
+        .expect("failed to run swhid binary");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let value: serde_json::Value =
+        serde_json::from_str(stderr.trim()).expect("stderr should be a JSON object");
+    assert_eq!(value["path"], "/does/not/exist/at/all");
+    assert!(!value["error"].as_str().unwrap().is_empty());
+}